@@ -1,12 +1,20 @@
 use anyhow::Context;
-use aoc::{days::day12, util::infra::*};
+use aoc::{
+    days::day12,
+    util::{image::image_path, infra::*},
+};
 
 fn main() {
     let (level, should_submit) = parse_args().unwrap();
     let input = include_str!("../../input/day12.txt");
     let data = match level {
         Level::One => day12::level1(input),
-        Level::Two => day12::level2(input),
+        Level::Two => {
+            if let Some(path) = image_path("day12.svg").unwrap() {
+                day12::regions_svg(input).save(path).unwrap();
+            }
+            day12::level2(input)
+        }
     };
     println!("{data}");
     if should_submit.is_some() {
@@ -1,12 +1,20 @@
 use anyhow::Context;
-use aoc::{days::day16, util::infra::*};
+use aoc::{
+    days::day16,
+    util::{image::image_path, infra::*},
+};
 
 fn main() {
     let (level, should_submit) = parse_args().unwrap();
     let input = include_str!("../../input/day16.txt");
     let data = match level {
         Level::One => day16::level1(input),
-        Level::Two => day16::level2(input),
+        Level::Two => {
+            if let Some(path) = image_path("day16.png").unwrap() {
+                day16::best_tiles_image(input).save(path).unwrap();
+            }
+            day16::level2(input)
+        }
     };
     println!("{data}");
     if should_submit.is_some() {
@@ -4,16 +4,33 @@ use aoc::{days::day17, util::infra::*};
 fn main() {
     let (level, should_submit) = parse_args().unwrap();
     let input = include_str!("../../input/day17.txt");
+    if std::env::var_os("TRACE").is_some() {
+        trace(input);
+    }
     let data = match level {
         Level::One => day17::level1(input),
         Level::Two => day17::level2(input),
     };
     println!("{data}");
     if should_submit.is_some() {
-        let day = 1;
+        let day = 17;
         let session = std::env::var("SESSION")
             .context("SESSION must be set to submit")
             .unwrap();
         let _ = submit(day, level, data, session);
     }
 }
+
+/// Prints the listing and an execution trace of the program to stderr.
+fn trace(input: &str) {
+    eprint!("{}", day17::listing(input));
+    let computer = day17::Computer::parse_input(input).expect("parse");
+    let mut debugger = day17::Debugger::new(&computer)
+        .with_trace()
+        .with_step_limit(1_000_000);
+    let result = debugger.run();
+    for entry in debugger.trace() {
+        eprintln!("{entry}");
+    }
+    eprintln!("{result:?} after {} steps", debugger.steps());
+}
@@ -1,12 +1,20 @@
 use anyhow::Context;
-use aoc::{days::day18, util::infra::*};
+use aoc::{
+    days::day18,
+    util::{image::image_path, infra::*},
+};
 
 fn main() {
     let (level, should_submit) = parse_args().unwrap();
     let input = include_str!("../../input/day18.txt");
     let data = match level {
         Level::One => day18::level1(input),
-        Level::Two => day18::level2(input),
+        Level::Two => {
+            if let Some(path) = image_path("day18.png").unwrap() {
+                day18::corrupted_bytes_image(input, 70).save(path).unwrap();
+            }
+            day18::level2(input)
+        }
     };
     println!("{data}");
     if should_submit.is_some() {
@@ -1,11 +1,19 @@
 use anyhow::Context;
-use aoc::{days::day20, util::infra::*};
+use aoc::{
+    days::day20,
+    util::{image::image_path, infra::*},
+};
 
 fn main() {
     let (level, should_submit) = parse_args().unwrap();
     let input = include_str!("../../input/day20.txt");
     let data = match level {
-        Level::One => day20::level1(input),
+        Level::One => {
+            if let Some(path) = image_path("day20.svg").unwrap() {
+                day20::racetrack_svg(input).save(path).unwrap();
+            }
+            day20::level1(input)
+        }
         Level::Two => day20::level2(input),
     };
     println!("{data}");
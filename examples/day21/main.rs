@@ -0,0 +1,19 @@
+use anyhow::Context;
+use aoc::{days::day21, util::infra::*};
+
+fn main() {
+    let (level, should_submit) = parse_args().unwrap();
+    let input = include_str!("../../input/day21.txt");
+    let data = match level {
+        Level::One => day21::level1(input),
+        Level::Two => day21::level2(input),
+    };
+    println!("{data}");
+    if should_submit.is_some() {
+        let day = 21;
+        let session = std::env::var("SESSION")
+            .context("SESSION must be set to submit")
+            .unwrap();
+        let _ = submit(day, level, data, session);
+    }
+}
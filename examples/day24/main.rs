@@ -5,12 +5,23 @@ fn main() {
     let (level, should_submit) = parse_args().unwrap();
     let input = include_str!("../../input/day24.txt");
     let data = match level {
-        Level::One => day24::level1(input),
-        Level::Two => day24::level2(input),
+        Level::One => day24::level1(input).to_string(),
+        Level::Two => {
+            if let Some(path) = std::env::var_os("DOT") {
+                day24::circuit_dot(input).write(path).unwrap();
+            }
+            if let Some(path) = std::env::var_os("VERILOG") {
+                std::fs::write(path, day24::circuit_verilog(input)).unwrap();
+            }
+            if let Some(path) = std::env::var_os("BLIF") {
+                std::fs::write(path, day24::circuit_blif(input)).unwrap();
+            }
+            day24::level2(input)
+        }
     };
     println!("{data}");
     if should_submit.is_some() {
-        let day = 1;
+        let day = 24;
         let session = std::env::var("SESSION")
             .context("SESSION must be set to submit")
             .unwrap();
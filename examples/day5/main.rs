@@ -5,7 +5,12 @@ fn main() {
     let (level, should_submit) = parse_args().unwrap();
     let input = include_str!("../../input/day5.txt");
     let data = match level {
-        Level::One => day5::level1(input),
+        Level::One => {
+            if let Some(path) = std::env::var_os("DOT") {
+                day5::rules_dot(input).write(path).unwrap();
+            }
+            day5::level1(input)
+        }
         Level::Two => day5::level2(input),
     };
     println!("{data}");
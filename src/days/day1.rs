@@ -12,13 +12,15 @@ pub fn level1(input: &str) -> u32 {
 }
 
 fn parse_numbers(input: &str) -> (Vec<u32>, Vec<u32>) {
-    let numbers: Vec<(u32, u32)> = input
-        .lines()
+    let numbers: Vec<(u32, u32)> = Input::new(input)
+        .ints()
         .map(|line| {
-            let (start, end) = line.split_once(' ').expect("no space");
+            let [start, end] = line[..] else {
+                panic!("expected two numbers per line, found {line:?}");
+            };
             (
-                start.trim().parse().expect("parse start"),
-                end.trim().parse().expect("parse end"),
+                start.try_into().expect("parse start"),
+                end.try_into().expect("parse end"),
             )
         })
         .collect();
@@ -1,102 +1,52 @@
-use std::collections::{HashSet, VecDeque};
+use crate::util::{
+    prelude::*,
+    search::{bfs, dijkstra},
+};
 
-use crate::util::prelude::*;
-
-pub fn level1(input: &str) -> usize {
-    let map = input
-        .lines()
-        .map(|line| {
-            line.chars()
-                .map(|c| c.to_digit(10).expect("digit"))
-                .collect_vec()
-        })
-        .collect_vec();
+fn parse_input(input: &str) -> Grid<u32> {
+    Input::new(input).grid(|c| c.to_digit(10)).expect("parse")
+}
 
-    let mut descendants = vec![vec![None; map[0].len()]; map.len()];
-    let mut queue = VecDeque::new();
+/// The positions one step further up the trail.
+fn uphill(map: &Grid<u32>, pos: &GridPos) -> Vec<GridPos> {
+    map.neighbors(pos)
+        .filter(|(_, height)| **height == map[*pos] + 1)
+        .map(|(new_pos, _)| new_pos)
+        .collect()
+}
 
-    for (y, row) in map.iter().enumerate() {
-        for (x, d) in row.iter().enumerate() {
-            if *d == 9 {
-                queue.push_back((x, y));
-                let mut end_descendants = HashSet::new();
-                end_descendants.insert((x, y));
-                descendants[y][x] = Some(end_descendants);
-            }
-        }
-    }
+pub fn level1(input: &str) -> usize {
+    let map = parse_input(input);
 
-    let mut count = 0;
-    while let Some((x, y)) = queue.pop_front() {
-        let height = map[y][x];
-        if height == 0 {
-            count += std::mem::take(&mut descendants[y][x])
-                .unwrap_or_default()
-                .len();
-            continue;
-        }
-        let neighbors = [(-1, 0), (1, 0), (0, -1), (0, 1)]
-            .into_iter()
-            .flat_map(|(dx, dy)| {
-                let child_x = x.checked_add_signed(dx)?;
-                let child_y = y.checked_add_signed(dy)?;
-                (map.get(child_y)?.get(child_x)? + 1 == height).then_some((child_x, child_y))
-            });
-        for (new_x, new_y) in neighbors {
-            descendants[new_y][new_x] = Some(
-                std::mem::take(&mut descendants[new_y][new_x])
-                    .unwrap_or_default()
-                    .union(descendants[y][x].as_ref().unwrap())
-                    .copied()
-                    .collect(),
-            );
-            queue.push_back((new_x, new_y));
-        }
-    }
-    count
+    map.find_all(&0)
+        .map(|trailhead| {
+            bfs([trailhead], |pos| uphill(&map, pos))
+                .into_keys()
+                .filter(|pos| map[*pos] == 9)
+                .count()
+        })
+        .sum()
 }
 
 pub fn level2(input: &str) -> usize {
-    let map = input
-        .lines()
-        .map(|line| {
-            line.chars()
-                .map(|c| c.to_digit(10).expect("digit"))
-                .collect_vec()
-        })
-        .collect_vec();
-
-    let mut path_counts = vec![vec![0; map[0].len()]; map.len()];
-    let queue = map
-        .iter()
-        .enumerate()
-        .flat_map(|(y, row)| row.iter().enumerate().map(move |(x, &d)| (d, x, y)))
-        .sorted()
-        .map(|(_, x, y)| (x, y))
-        .collect_vec();
+    let map = parse_input(input);
 
-    let mut count = 0;
-    for (x, y) in queue {
-        let height = map[y][x];
-        if height == 9 {
-            count += path_counts[y][x];
-            continue;
-        }
-        if height == 0 {
-            path_counts[y][x] = 1;
-        }
-        let neighbors = [(-1, 0), (1, 0), (0, -1), (0, 1)]
-            .into_iter()
-            .flat_map(|(dx, dy)| {
-                let child_x = x.checked_add_signed(dx)?;
-                let child_y = y.checked_add_signed(dy)?;
-                (*map.get(child_y)?.get(child_x)? == height + 1).then_some((child_x, child_y))
-            });
-        for (new_x, new_y) in neighbors {
-            path_counts[new_y][new_x] += path_counts[y][x];
-        }
-    }
-    count
+    // Every trail climbs by one per step, so all trails to a summit are shortest paths.
+    let paths = dijkstra(
+        map.find_all(&0),
+        |pos| {
+            uphill(&map, pos)
+                .into_iter()
+                .map(|new_pos| (new_pos, 1usize))
+        },
+        |_| false,
+    );
+    paths
+        .path_counts()
+        .into_iter()
+        .filter(|(pos, _)| map[*pos] == 9)
+        .map(|(_, count)| count)
+        .sum()
 }
 
 #[cfg(test)]
@@ -19,9 +19,9 @@ where
 }
 
 fn split_stones(input: &str, iterations: usize) -> usize {
-    let mut sum_map: SumMap<u64> = input
-        .trim()
-        .split(' ')
+    let mut sum_map: SumMap<u64> = Input::new(input)
+        .as_str()
+        .split_whitespace()
         .map(|number| (number.parse().expect("Input only has numbers"), 1))
         .collect();
     for _ in 0..iterations {
@@ -73,6 +73,6 @@ mod test {
     #[test]
     fn level2_given_example() {
         let test_input = include_str!("./test_input/day11.txt");
-        assert_eq!(level2(test_input), 0)
+        assert_eq!(level2(test_input), 65601038650482)
     }
 }
@@ -1,113 +1,79 @@
-use std::collections::VecDeque;
+use crate::util::{
+    image::{hash_color, Rgb, Svg},
+    prelude::*,
+    regions::Regions,
+};
 
-use crate::util::prelude::*;
-
-pub fn level1(input: &str) -> usize {
-    let map = input
-        .lines()
-        .map(|line| line.chars().collect_vec())
-        .collect_vec();
-    let width = map[0].len();
-    let height = map.len();
-
-    let mut result = 0;
-    let mut visited = vec![vec![false; width]; height];
+fn parse_input(input: &str) -> Grid<char> {
+    Input::new(input)
+        .grid(|c| c.is_ascii_uppercase().then_some(c))
+        .expect("parse")
+}
 
-    for (y, row) in map.iter().enumerate() {
-        for (x, c) in row.iter().enumerate() {
-            let mut area = 0;
-            let mut perimeter = 0;
-            let mut queue = VecDeque::new();
-            queue.push_back((x, y));
-            while let Some((x, y)) = queue.pop_front() {
-                if visited[y][x] {
-                    continue;
-                }
-                visited[y][x] = true;
-                let neighbors = [(1, 0), (-1, 0), (0, 1), (0, -1)]
-                    .into_iter()
-                    .flat_map(|(dx, dy)| {
-                        let next_x = x.checked_add_signed(dx)?;
-                        let next_y = y.checked_add_signed(dy)?;
-                        (map.get(next_y)?.get(next_x)? == c).then_some((next_x, next_y))
-                    })
-                    .collect_vec();
-                area += 1;
-                perimeter += 4 - neighbors.len();
+fn regions(map: &Grid<char>) -> Regions {
+    Regions::partition(map, |a, b| a == b)
+}
 
-                queue.extend(neighbors);
-            }
-            result += area * perimeter;
-        }
-    }
-    result
+pub fn level1(input: &str) -> usize {
+    let map = parse_input(input);
+    regions(&map)
+        .regions()
+        .iter()
+        .map(|region| region.area * region.perimeter)
+        .sum()
 }
 
 pub fn level2(input: &str) -> usize {
-    let map = input
-        .lines()
-        .map(|line| line.chars().collect_vec())
-        .collect_vec();
-    let width = map[0].len();
-    let height = map.len();
-
-    let mut result = 0;
-    let mut visited = vec![vec![false; width]; height];
+    let map = parse_input(input);
+    regions(&map)
+        .regions()
+        .iter()
+        .map(|region| region.area * region.sides)
+        .sum()
+}
 
-    for (y, row) in map.iter().enumerate() {
-        for (x, c) in row.iter().enumerate() {
-            let mut area = 0;
-            let mut corners = 0;
-            let mut queue = VecDeque::new();
-            queue.push_back((x, y));
-            while let Some((x, y)) = queue.pop_front() {
-                if visited[y][x] {
-                    continue;
-                }
-                visited[y][x] = true;
-                let (neighbor_pos, neighbors): (Vec<_>, Vec<_>) =
-                    [(1, 0), (0, 1), (-1, 0), (0, -1)]
-                        .into_iter()
-                        .enumerate()
-                        .flat_map(|(i, (dx, dy))| {
-                            let next_x = x.checked_add_signed(dx)?;
-                            let next_y = y.checked_add_signed(dy)?;
-                            (map.get(next_y)?.get(next_x)? == c).then_some((i, (next_x, next_y)))
-                        })
-                        .unzip();
-                area += 1;
-                let convex_corners = (0..4)
-                    .filter(|corner_pos| {
-                        !(neighbor_pos.contains(corner_pos)
-                            || neighbor_pos.contains(&((corner_pos + 1) % 4)))
-                    })
-                    .count();
-                let concave_corners = [-1, 1]
-                    .into_iter()
-                    .cartesian_product([-1, 1])
-                    .flat_map(|(dx, dy)| {
-                        let next_x = x.checked_add_signed(dx)?;
-                        let next_y = y.checked_add_signed(dy)?;
-                        (map.get(next_y)?.get(next_x)? != c
-                            && map.get(y)?.get(next_x)? == c
-                            && map.get(next_y)?.get(x)? == c)
-                            .then_some(())
-                    })
-                    .count();
-                corners += convex_corners + concave_corners;
+/// The garden with each plot coloured by its plant and fences drawn between different plants.
+pub fn regions_svg(input: &str) -> Svg {
+    let map = parse_input(input);
+    let mut svg = Svg::new(map.length, map.height, 16);
+    svg.cells(|pos| Some(hash_color(map[pos] as u32)));
 
-                queue.extend(neighbors.into_iter().filter(|(x, y)| !visited[*y][*x]));
-            }
-            result += area * corners;
+    for (GridPos { x, y }, c) in map.enumerate() {
+        let mut fences = Vec::new();
+        if y == 0 || map[GridPos { x, y: y - 1 }] != *c {
+            fences.push(([x, y], [x + 1, y]));
+        }
+        if x == 0 || map[GridPos { x: x - 1, y }] != *c {
+            fences.push(([x, y], [x, y + 1]));
+        }
+        if y + 1 == map.height {
+            fences.push(([x, y + 1], [x + 1, y + 1]));
+        }
+        if x + 1 == map.length {
+            fences.push(([x + 1, y], [x + 1, y + 1]));
+        }
+        for (from, to) in fences {
+            svg.segment(from, to, Rgb::BLACK, 2.0);
         }
     }
-    result
+    svg
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
 
+    #[test]
+    fn regions_image() {
+        let test_input = include_str!("./test_input/day12.txt");
+        let svg = regions_svg(test_input).to_svg_string();
+        assert!(svg.contains(r#"width="64" height="64" viewBox="0 0 64 64""#));
+        let fill = hash_color('A' as u32).hex();
+        assert!(svg.contains(&format!(
+            r#"<rect x="48" y="0" width="16" height="16" fill="{fill}"/>"#
+        )));
+    }
+
     #[test]
     fn level1_given_example_small() {
         let test_input = include_str!("./test_input/day12.txt");
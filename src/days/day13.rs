@@ -1,59 +1,323 @@
+use std::fmt::Display;
+
 use nom::{
-    character::complete::{i64, newline},
-    combinator::eof,
+    character::complete::{alphanumeric1, i64, newline},
+    multi::separated_list1,
     sequence::{separated_pair, tuple},
     IResult, Parser,
 };
-use nom_supreme::{
-    error::ErrorTree, final_parser::final_parser, multi::collect_separated_terminated,
-    tag::complete::tag, ParserExt,
+use nom_supreme::{error::ErrorTree, tag::complete::tag, ParserExt};
+
+use crate::util::{
+    input::blocks_of,
+    math::{extended_gcd, solve_integer, MathError},
+    prelude::*,
 };
 
-use crate::util::prelude::*;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Button {
+    pub step: [i64; 2],
+    /// Tokens per press, never negative.
+    pub cost: i64,
+}
 
-#[derive(Debug, Clone, Copy)]
-struct Machine {
-    buttons: [[i64; 2]; 2],
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Machine {
+    /// At least two buttons.
+    buttons: Vec<Button>,
     prize: [i64; 2],
 }
 
+/// The cheapest way to reach a prize, with one press count per button.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solution {
+    pub presses: Vec<i64>,
+    pub cost: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolveError {
+    Math(MathError),
+    /// Without a press limit, the presses of this extra button are not bounded by the prize.
+    Unbounded {
+        button: usize,
+    },
+}
+
+impl From<MathError> for SolveError {
+    fn from(error: MathError) -> Self {
+        SolveError::Math(error)
+    }
+}
+
+impl Display for SolveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SolveError::Math(error) => error.fmt(f),
+            SolveError::Unbounded { button } => {
+                write!(f, "the presses of button {button} need a limit")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SolveError {}
+
+fn floor_div(a: i128, b: i128) -> i128 {
+    if b < 0 {
+        (-a).div_euclid(-b)
+    } else {
+        a.div_euclid(b)
+    }
+}
+
+fn ceil_div(a: i128, b: i128) -> i128 {
+    -floor_div(-a, b)
+}
+
+/// The range of `n` with `0 <= start + step * n <= limit`, as optional bounds. `None` if there is
+/// no such `n`.
+fn press_range(start: i128, step: i128, limit: Option<i64>) -> Option<[Option<i128>; 2]> {
+    let limit = limit.map(i128::from);
+    if step == 0 {
+        let fits = start >= 0 && limit.is_none_or(|limit| start <= limit);
+        return fits.then_some([None, None]);
+    }
+    let zero = -start;
+    let top = limit.map(|limit| limit - start);
+    let range = if step > 0 {
+        [
+            Some(ceil_div(zero, step)),
+            top.map(|top| floor_div(top, step)),
+        ]
+    } else {
+        [
+            top.map(|top| ceil_div(top, step)),
+            Some(floor_div(zero, step)),
+        ]
+    };
+    Some(range)
+}
+
+fn total_cost(buttons: &[Button], presses: &[i64]) -> Result<i64, MathError> {
+    buttons
+        .iter()
+        .zip(presses)
+        .try_fold(0i64, |total, (button, presses)| {
+            button
+                .cost
+                .checked_mul(*presses)
+                .and_then(|cost| total.checked_add(cost))
+                .ok_or(MathError::Overflow)
+        })
+}
+
+/// The cheapest presses of two parallel buttons. Reduced to one coordinate, the solutions of
+/// `a * u + b * v = target` are `a = a0 + n * v / g` and `b = b0 - n * u / g`, and the cost is
+/// linear in `n`, so the optimum is at one end of the range where both counts are allowed.
+fn cheapest_collinear(
+    [first, second]: [Button; 2],
+    target: [i64; 2],
+    limit: Option<i64>,
+) -> Result<Option<[i64; 2]>, MathError> {
+    // Parallel non-zero buttons are zero in the same coordinates.
+    let Some(axis) = (0..2).find(|&axis| first.step[axis] != 0 || second.step[axis] != 0) else {
+        return Ok((target == [0, 0]).then_some([0, 0]));
+    };
+    let (u, v) = (first.step[axis], second.step[axis]);
+    let (g, s, t) = extended_gcd(u, v)?;
+    if target[axis] % g != 0 {
+        return Ok(None);
+    }
+    let scale = (target[axis] / g) as i128;
+    let starts = [s as i128 * scale, t as i128 * scale];
+    let steps = [(v / g) as i128, -(u / g) as i128];
+    let (Some([low_a, high_a]), Some([low_b, high_b])) = (
+        press_range(starts[0], steps[0], limit),
+        press_range(starts[1], steps[1], limit),
+    ) else {
+        return Ok(None);
+    };
+    let low = low_a.into_iter().chain(low_b).max();
+    let high = high_a.into_iter().chain(high_b).min();
+    if let (Some(low), Some(high)) = (low, high) {
+        if low > high {
+            return Ok(None);
+        }
+    }
+    let slope = first.cost as i128 * steps[0] + second.cost as i128 * steps[1];
+    // With non-negative costs, the cost never falls towards an open end.
+    let n = if slope > 0 {
+        low.or(high)
+    } else {
+        high.or(low)
+    }
+    .unwrap_or(0);
+    let presses = [0, 1].map(|i| i64::try_from(starts[i] + steps[i] * n));
+    let [Ok(a), Ok(b)] = presses else {
+        return Err(MathError::Overflow);
+    };
+    // The reduction to one coordinate holds only if the target is on the buttons' line.
+    let reaches = (0..2).all(|axis| {
+        first.step[axis]
+            .checked_mul(a)
+            .zip(second.step[axis].checked_mul(b))
+            .and_then(|(x, y)| x.checked_add(y))
+            == Some(target[axis])
+    });
+    Ok(reaches.then_some([a, b]))
+}
+
+/// The cheapest presses of two buttons that reach `target`, each at most `limit` times.
+fn cheapest_pair(
+    pair: [Button; 2],
+    target: [i64; 2],
+    limit: Option<i64>,
+) -> Result<Option<[i64; 2]>, MathError> {
+    let [a, b] = pair.map(|button| button.step);
+    let matrix = [vec![a[0], b[0]], vec![a[1], b[1]]];
+    match solve_integer(&matrix, &target) {
+        Ok(presses) => Ok(presses
+            .filter(|presses| {
+                presses
+                    .iter()
+                    .all(|presses| *presses >= 0 && limit.is_none_or(|limit| *presses <= limit))
+            })
+            .map(|presses| [presses[0], presses[1]])),
+        Err(MathError::Singular) => cheapest_collinear(pair, target, limit),
+        Err(error) => Err(error),
+    }
+}
+
 impl Machine {
-    fn winning_combination(&self) -> Option<[i64; 2]> {
-        let determinant =
-            self.buttons[0][0] * self.buttons[1][1] - self.buttons[0][1] * self.buttons[1][0];
-        let multiplied_with_transpose = [
-            self.buttons[1][1] * self.prize[0] - self.buttons[1][0] * self.prize[1],
-            -self.buttons[0][1] * self.prize[0] + self.buttons[0][0] * self.prize[1],
-        ];
-        multiplied_with_transpose
-            .iter()
-            .all(|coord| coord % determinant == 0)
-            .then_some(multiplied_with_transpose.map(|coord| coord / determinant))
+    pub fn new(buttons: Vec<Button>, prize: [i64; 2]) -> Self {
+        assert!(buttons.len() >= 2, "a machine has at least two buttons");
+        assert!(
+            buttons.iter().all(|button| button.cost >= 0),
+            "costs are not negative"
+        );
+        Self { buttons, prize }
     }
 
-    fn correct_unit_conversion(self) -> Self {
-        Self {
-            prize: self.prize.map(|entry| entry + 10000000000000),
+    /// Replaces the cost of every button, in input order.
+    pub fn with_costs(mut self, costs: &[i64]) -> Self {
+        assert_eq!(costs.len(), self.buttons.len(), "one cost per button");
+        assert!(
+            costs.iter().all(|cost| *cost >= 0),
+            "costs are not negative"
+        );
+        for (button, cost) in self.buttons.iter_mut().zip(costs) {
+            button.cost = *cost;
+        }
+        self
+    }
+
+    pub fn correct_unit_conversion(self) -> Result<Self, MathError> {
+        let [x, y] = self.prize.map(|entry| entry.checked_add(10000000000000));
+        Ok(Self {
+            prize: [x.ok_or(MathError::Overflow)?, y.ok_or(MathError::Overflow)?],
             ..self
+        })
+    }
+
+    /// The cheapest way to win, pressing each button at most `limit` times. Beyond the first two
+    /// buttons, this tries every press count of the extra buttons and solves the first two
+    /// exactly for the rest, so it is only meant for a few extra buttons.
+    pub fn cheapest(&self, limit: Option<i64>) -> Result<Option<Solution>, SolveError> {
+        let [first, second, extra @ ..] = &self.buttons[..] else {
+            panic!("a machine has at least two buttons");
+        };
+        let mut best = None;
+        let mut presses = Vec::with_capacity(extra.len());
+        self.search(
+            [*first, *second],
+            extra,
+            self.prize,
+            limit,
+            &mut presses,
+            &mut best,
+        )?;
+        Ok(best)
+    }
+
+    fn search(
+        &self,
+        pair: [Button; 2],
+        extra: &[Button],
+        target: [i64; 2],
+        limit: Option<i64>,
+        presses: &mut Vec<i64>,
+        best: &mut Option<Solution>,
+    ) -> Result<(), SolveError> {
+        let Some((button, rest)) = extra.split_first() else {
+            if let Some(pair_presses) = cheapest_pair(pair, target, limit)? {
+                let all = pair_presses
+                    .iter()
+                    .chain(presses.iter())
+                    .copied()
+                    .collect_vec();
+                let cost = total_cost(&self.buttons, &all)?;
+                if best.as_ref().is_none_or(|best| cost < best.cost) {
+                    *best = Some(Solution { presses: all, cost });
+                }
+            }
+            return Ok(());
+        };
+        // Without a limit, a coordinate in which no remaining button moves backwards bounds
+        // the presses.
+        let remaining = pair.iter().chain(extra).collect_vec();
+        let bound = limit.or_else(|| {
+            (0..2)
+                .filter(|axis| {
+                    button.step[*axis] > 0 && remaining.iter().all(|other| other.step[*axis] >= 0)
+                })
+                .map(|axis| target[axis].div_euclid(button.step[axis]))
+                .min()
+        });
+        let bound = bound.ok_or(SolveError::Unbounded {
+            button: 2 + presses.len(),
+        })?;
+        for count in 0..=bound {
+            let next = [0, 1].map(|axis| {
+                button.step[axis]
+                    .checked_mul(count)
+                    .and_then(|moved| target[axis].checked_sub(moved))
+            });
+            let [Some(x), Some(y)] = next else {
+                return Err(MathError::Overflow.into());
+            };
+            presses.push(count);
+            self.search(pair, rest, [x, y], limit, presses, best)?;
+            presses.pop();
         }
+        Ok(())
     }
 }
 
-fn parse_machine(input: &str) -> IResult<&str, Machine, ErrorTree<&str>> {
+/// A button line like `Button A: X+94, Y+34`. Button `A` costs 3 tokens, all others cost 1.
+fn parse_button(input: &str) -> IResult<&str, Button, ErrorTree<&str>> {
     tuple((
+        alphanumeric1
+            .preceded_by(tag("Button "))
+            .terminated(tag(": ")),
         separated_pair(
-            i64.preceded_by(tag("X+")),
+            i64.preceded_by(tag("X")),
             tag(", "),
-            i64.preceded_by(tag("Y+")),
-        )
-        .preceded_by(tag("Button A: ")),
-        separated_pair(
-            i64.preceded_by(tag("X+")),
-            tag(", "),
-            i64.preceded_by(tag("Y+")),
-        )
-        .preceded_by(tag("Button B: "))
-        .preceded_by(newline),
+            i64.preceded_by(tag("Y")),
+        ),
+    ))
+    .map(|(label, (x, y))| Button {
+        step: [x, y],
+        cost: if label == "A" { 3 } else { 1 },
+    })
+    .parse(input)
+}
+
+fn parse_machine(input: &str) -> IResult<&str, Machine, ErrorTree<&str>> {
+    tuple((
+        separated_list1(newline, parse_button)
+            .verify(|buttons: &Vec<Button>| buttons.len() >= 2)
+            .context("at least two buttons"),
         separated_pair(
             i64.preceded_by(tag("X=")),
             tag(", "),
@@ -62,28 +326,21 @@ fn parse_machine(input: &str) -> IResult<&str, Machine, ErrorTree<&str>> {
         .preceded_by(tag("Prize: "))
         .preceded_by(newline),
     ))
-    .map(|(button_a, button_b, prize)| Machine {
-        buttons: [[button_a.0, button_a.1], [button_b.0, button_b.1]],
-        prize: [prize.0, prize.1],
-    })
+    .map(|(buttons, prize)| Machine::new(buttons, [prize.0, prize.1]))
     .parse(input)
 }
 
-fn parse_input(input: &str) -> Result<Vec<Machine>, ErrorTree<&str>> {
-    final_parser(collect_separated_terminated(
-        parse_machine,
-        newline.precedes(newline),
-        eof.opt_preceded_by(newline),
-    ))(input)
+/// All machines, with the default costs of 3 tokens for button `A` and 1 for any other button.
+pub fn parse_input(input: &str) -> ParseFinalResult<'_, Vec<Machine>> {
+    Input::new(input).parse(blocks_of(parse_machine))
 }
 
 pub fn level1(input: &str) -> i64 {
     let input = parse_input(input).expect("parse");
     input
         .into_iter()
-        .flat_map(|machine| machine.winning_combination())
-        .filter(|combination| combination[0] <= 100 && combination[1] <= 100)
-        .map(|combination| 3 * combination[0] + combination[1])
+        .flat_map(|machine| machine.cheapest(Some(100)).expect("solve"))
+        .map(|solution| solution.cost)
         .sum()
 }
 
@@ -91,8 +348,11 @@ pub fn level2(input: &str) -> i64 {
     let input = parse_input(input).expect("parse");
     input
         .into_iter()
-        .flat_map(|machine| machine.correct_unit_conversion().winning_combination())
-        .map(|combination| 3 * combination[0] + combination[1])
+        .flat_map(|machine| {
+            let machine = machine.correct_unit_conversion().expect("prize fits");
+            machine.cheapest(None).expect("solve")
+        })
+        .map(|solution| solution.cost)
         .sum()
 }
 
@@ -105,4 +365,74 @@ mod test {
         let test_input = include_str!("./test_input/day13.txt");
         assert_eq!(level1(test_input), 480)
     }
+
+    fn machine(buttons: &[[i64; 2]], prize: [i64; 2]) -> Machine {
+        let buttons = buttons
+            .iter()
+            .map(|step| Button {
+                step: *step,
+                cost: 1,
+            })
+            .collect();
+        Machine::new(buttons, prize)
+    }
+
+    #[test]
+    fn collinear_buttons() {
+        // 3a + b along the line: five B presses are cheaper than two A presses and one B.
+        let collinear = machine(&[[2, 4], [1, 2]], [5, 10]).with_costs(&[3, 1]);
+        let solution = collinear.cheapest(None).unwrap().unwrap();
+        assert_eq!(solution.presses, [0, 5]);
+        assert_eq!(solution.cost, 5);
+        let solution = collinear.clone().with_costs(&[1, 1]).cheapest(None);
+        assert_eq!(solution.unwrap().unwrap().presses, [2, 1]);
+        assert_eq!(
+            collinear.cheapest(Some(4)).unwrap().unwrap().presses,
+            [1, 3]
+        );
+        let off_line = machine(&[[2, 4], [1, 2]], [5, 11]);
+        assert_eq!(off_line.cheapest(None), Ok(None));
+        let no_multiple = machine(&[[2, 4], [4, 8]], [5, 10]);
+        assert_eq!(no_multiple.cheapest(None), Ok(None));
+        let opposite = machine(&[[3, 3], [-2, -2]], [1, 1]).with_costs(&[1, 1]);
+        assert_eq!(opposite.cheapest(None).unwrap().unwrap().presses, [1, 1]);
+    }
+
+    #[test]
+    fn negative_presses_and_overflow() {
+        // The unique solution presses B minus once.
+        let backwards = machine(&[[1, 0], [1, 1]], [3, -1]);
+        assert_eq!(backwards.cheapest(None), Ok(None));
+        let far = machine(&[[1, 0], [0, 1]], [i64::MAX, 1]);
+        assert_eq!(
+            far.clone().correct_unit_conversion(),
+            Err(MathError::Overflow)
+        );
+        let expensive = far.with_costs(&[2, 1]);
+        assert_eq!(
+            expensive.cheapest(None),
+            Err(SolveError::Math(MathError::Overflow))
+        );
+    }
+
+    #[test]
+    fn extra_buttons() {
+        let input = "Button A: X+3, Y+0\nButton B: X+0, Y+3\nButton C: X+1, Y+1\n\
+                     Prize: X=4, Y=4\n";
+        let machines = parse_input(input).unwrap();
+        let solution = machines[0].cheapest(None).unwrap().unwrap();
+        assert_eq!(solution.presses, [0, 0, 4]);
+        assert_eq!(solution.cost, 4);
+        let solution = machines[0].clone().with_costs(&[1, 1, 3]).cheapest(None);
+        assert_eq!(solution.unwrap().unwrap().presses, [1, 1, 1]);
+        let sliding = machine(&[[-1, 0], [0, 1], [1, -1]], [2, 2]);
+        assert_eq!(
+            sliding.cheapest(None),
+            Err(SolveError::Unbounded { button: 2 })
+        );
+        assert_eq!(
+            sliding.cheapest(Some(5)).unwrap().unwrap().presses,
+            [0, 4, 2]
+        );
+    }
 }
@@ -1,7 +1,6 @@
 use std::{
     io::{self, stdout, Write},
     time::Duration,
-    usize,
 };
 
 use crossterm::{
@@ -12,84 +11,55 @@ use crossterm::{
     terminal::{self, disable_raw_mode, enable_raw_mode, Clear, ClearType, SetSize},
     ExecutableCommand, QueueableCommand,
 };
-use nom::{
-    character::complete::{i32, newline},
-    combinator::eof,
-    sequence::separated_pair,
-    IResult, Parser,
-};
-use nom_supreme::{
-    error::ErrorTree, final_parser::final_parser, multi::collect_separated_terminated,
-    tag::complete::tag, ParserExt,
-};
+use nom::{sequence::separated_pair, IResult, Parser};
+use nom_supreme::{error::ErrorTree, tag::complete::tag, ParserExt};
 
-use crate::util::prelude::*;
+use crate::util::{
+    input::{int_pair, lines_of},
+    math::{crt, MathError},
+    prelude::*,
+    torus::Torus,
+    vec2::Vec2,
+};
 
 #[derive(Debug, Clone)]
 struct Robot {
-    position: [i32; 2],
-    velocity: [i32; 2],
+    position: Vec2,
+    velocity: Vec2,
 }
 
 impl Robot {
-    fn step(&mut self, seconds: i32, bounds: [i32; 2]) {
-        self.position = [0, 1].map(|i| {
-            let result = (self.position[i] + seconds * self.velocity[i]) % bounds[i];
-            if result < 0 {
-                result + bounds[i]
-            } else {
-                result
-            }
-        })
-    }
-
-    fn quadrant(&self, bounds: [i32; 2]) -> Option<[i32; 2]> {
-        let mut result = [0; 2];
-        for i in 0..2 {
-            result[i] = match (2 * self.position[i] + 1).cmp(&bounds[i]) {
-                std::cmp::Ordering::Less => Some(0),
-                std::cmp::Ordering::Equal => None,
-                std::cmp::Ordering::Greater => Some(1),
-            }?
-        }
-        Some(result)
+    fn step(&mut self, seconds: i64, torus: &Torus) {
+        self.position = torus.advance(self.position, self.velocity, seconds);
     }
 }
 
-fn parse_robots(input: &str) -> Result<Vec<Robot>, ErrorTree<&str>> {
+fn parse_robots(input: &str) -> ParseFinalResult<'_, Vec<Robot>> {
     fn robot_parser(input: &str) -> IResult<&str, Robot, ErrorTree<&str>> {
         separated_pair(
-            separated_pair(i32, tag(","), i32)
-                .preceded_by(tag("p="))
-                .context("position"),
+            int_pair(",").preceded_by(tag("p=")).context("position"),
             tag(" "),
-            separated_pair(i32, tag(","), i32)
-                .preceded_by(tag("v="))
-                .context("velocity"),
+            int_pair(",").preceded_by(tag("v=")).context("velocity"),
         )
         .map(|(p, v)| Robot {
-            position: [p.0, p.1],
-            velocity: [v.0, v.1],
+            position: Vec2::new(p.0, p.1),
+            velocity: Vec2::new(v.0, v.1),
         })
         .context("robot")
         .parse(input)
     }
-    final_parser(collect_separated_terminated(
-        robot_parser,
-        newline,
-        eof.opt_preceded_by(newline),
-    ))(input)
+    Input::new(input).parse(lines_of(robot_parser))
 }
 
-fn predict_positions(input: &str, width: i32, height: i32) -> usize {
+fn predict_positions(input: &str, width: i64, height: i64) -> usize {
     let robots = parse_robots(input).expect("parse");
     let steps = 100;
-    let bounds = [width, height];
+    let torus = Torus::new(width, height);
     robots
         .into_iter()
         .flat_map(|mut robot| {
-            robot.step(steps, bounds);
-            robot.quadrant(bounds)
+            robot.step(steps, &torus);
+            torus.quadrant(robot.position)
         })
         .counts()
         .into_values()
@@ -100,13 +70,37 @@ pub fn level1(input: &str) -> usize {
     predict_positions(input, 101, 103)
 }
 
-fn print_robots<'a>(robots: impl IntoIterator<Item = &'a Robot>, step: i32) -> io::Result<()> {
+/// The time within one period of an axis at which the robots' coordinates on it are least
+/// spread out, measured by `n` times their variance.
+fn clustered_time(robots: &[Robot], period: i64, coordinate: impl Fn(Vec2) -> i64) -> i64 {
+    let n = robots.len() as i64;
+    (0..period)
+        .min_by_key(|t| {
+            let (sum, sum_of_squares) = robots.iter().fold((0, 0), |(sum, squares), robot| {
+                let c = coordinate(robot.position + robot.velocity * *t).rem_euclid(period);
+                (sum + c, squares + c * c)
+            });
+            n * sum_of_squares - sum * sum
+        })
+        .expect("the torus has a positive size")
+}
+
+/// The first step at which the robots form a picture. Every robot moves periodically along each
+/// axis, so the step where both coordinates cluster is found per axis and combined with the
+/// Chinese remainder theorem.
+fn tree_step(robots: &[Robot], torus: &Torus) -> Result<i64, MathError> {
+    let x = clustered_time(robots, torus.size.x, |pos| pos.x);
+    let y = clustered_time(robots, torus.size.y, |pos| pos.y);
+    Ok(crt([(x, torus.size.x), (y, torus.size.y)])?.0)
+}
+
+fn print_robots<'a>(robots: impl IntoIterator<Item = &'a Robot>, step: i64) -> io::Result<()> {
     let mut stdout = stdout();
     stdout.execute(Clear(ClearType::All))?;
     for robot in robots {
         execute!(
             stdout,
-            MoveTo(robot.position[0] as u16, robot.position[1] as u16 + 1,),
+            MoveTo(robot.position.x as u16, robot.position.y as u16 + 1),
             PrintStyledContent("█".green()),
             MoveTo(0, 0),
             PrintStyledContent(step.to_string().red()),
@@ -116,27 +110,27 @@ fn print_robots<'a>(robots: impl IntoIterator<Item = &'a Robot>, step: i32) -> i
     Ok(())
 }
 
-fn robot_explorer(input: &str, width: i32, height: i32) -> io::Result<usize> {
+fn robot_explorer(input: &str, width: i64, height: i64, start: i64) -> io::Result<usize> {
     let robots = parse_robots(input).expect("parse");
-    let bounds = [width, height];
-    let mut step = 0;
+    let torus = Torus::new(width, height);
+    let mut step = start;
     let size = crossterm::terminal::size()?;
     execute!(io::stdout(), SetSize(width as u16, height as u16))?;
     enable_raw_mode()?;
     let result = 'steps: loop {
         let mut robots = robots.clone();
         for robot in &mut robots {
-            robot.step(step, bounds);
+            robot.step(step, &torus);
         }
         print_robots(&robots, step).unwrap();
         let next = loop {
             if let crossterm::event::Event::Key(event) = crossterm::event::read().unwrap() {
                 let mut step_size = 1;
                 if event.modifiers.contains(KeyModifiers::SHIFT) {
-                    step_size *= bounds[0];
+                    step_size *= torus.size.x;
                 }
                 if event.modifiers.contains(KeyModifiers::CONTROL) {
-                    step_size *= bounds[1];
+                    step_size *= torus.size.y;
                 }
                 match event.code {
                     crossterm::event::KeyCode::Left => break -step_size,
@@ -148,7 +142,7 @@ fn robot_explorer(input: &str, width: i32, height: i32) -> io::Result<usize> {
         };
         step += next;
         for robot in &mut robots {
-            robot.step(next as i32, bounds);
+            robot.step(next, &torus);
         }
     };
     disable_raw_mode()?;
@@ -157,7 +151,12 @@ fn robot_explorer(input: &str, width: i32, height: i32) -> io::Result<usize> {
 }
 
 pub fn level2(input: &str) -> usize {
-    robot_explorer(input, 101, 103).expect("interactive search failed")
+    let robots = parse_robots(input).expect("parse");
+    let step = tree_step(&robots, &Torus::new(101, 103)).expect("widths are coprime");
+    if std::env::var_os("PLOT").is_some() {
+        return robot_explorer(input, 101, 103, step).expect("interactive search failed");
+    }
+    step as usize
 }
 
 #[cfg(test)]
@@ -169,4 +168,22 @@ mod test {
         let test_input = include_str!("./test_input/day14.txt");
         assert_eq!(predict_positions(test_input, 11, 7), 12)
     }
+
+    #[test]
+    fn tree_step_finds_constructed_picture() {
+        // Thirty robots that all meet in a 3x3 block in the middle of the field at step 40,
+        // started by running each one back from its place in the block.
+        let torus = Torus::new(11, 7);
+        let robots = (0..30)
+            .map(|i| {
+                let target = Vec2::new(4 + i % 3, 2 + i / 3 % 3);
+                let velocity = Vec2::new(i * 7 % 11 - 5, (i * 4 + 1) % 7 - 3);
+                Robot {
+                    position: torus.advance(target, velocity, -40),
+                    velocity,
+                }
+            })
+            .collect_vec();
+        assert_eq!(tree_step(&robots, &torus), Ok(40));
+    }
 }
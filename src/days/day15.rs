@@ -1,5 +1,5 @@
 use std::{
-    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    collections::{BTreeMap, BTreeSet, HashSet},
     io::{self, stdout, Write},
     iter::repeat,
     time::Duration,
@@ -12,41 +12,10 @@ use crossterm::{
     terminal::{Clear, ClearType},
 };
 
-use crate::util::prelude::*;
-
-#[derive(Debug, Clone, Copy)]
-enum Direction {
-    Up,
-    Right,
-    Down,
-    Left,
-}
-
-impl Direction {
-    fn step(&self, pos: [i16; 2]) -> [i16; 2] {
-        let delta = match self {
-            Direction::Up => [0, -1],
-            Direction::Right => [1, 0],
-            Direction::Down => [0, 1],
-            Direction::Left => [-1, 0],
-        };
-        [0, 1].map(|i| pos[i] + delta[i])
-    }
-}
-
-impl TryFrom<char> for Direction {
-    type Error = String;
-
-    fn try_from(value: char) -> Result<Self, Self::Error> {
-        match value {
-            '^' => Ok(Direction::Up),
-            '>' => Ok(Direction::Right),
-            'v' => Ok(Direction::Down),
-            '<' => Ok(Direction::Left),
-            c => Err(format!("unexpected character: {c}")),
-        }
-    }
-}
+use crate::util::{
+    prelude::*,
+    sparse::{step, SparseGrid, SparsePos},
+};
 
 #[derive(Debug, Clone, Copy)]
 enum Cell {
@@ -55,42 +24,38 @@ enum Cell {
 }
 
 struct Map {
-    robot_pos: [i16; 2],
-    cells: HashMap<[i16; 2], Cell>,
-    cell_width: i16,
+    robot_pos: SparsePos,
+    cells: SparseGrid<Cell>,
+    cell_width: i32,
 }
 
 impl Map {
-    fn parse_input(input: &str, cell_width: i16) -> Self {
-        let mut robot_pos = None;
-        let mut cells = HashMap::new();
-
-        for (y, line) in input.lines().enumerate() {
-            for (x, c) in line.chars().enumerate() {
-                let pos = [cell_width * x as i16, y as i16];
-                match c {
-                    'O' => {
-                        cells.insert(pos, Cell::Box);
-                    }
-                    '#' => {
-                        cells.insert(pos, Cell::Wall);
-                    }
-                    '@' => {
-                        robot_pos = Some(pos);
-                    }
-                    '.' => {}
-                    c => panic!("Unexpected map input: {c}"),
-                }
-            }
-        }
+    /// Parses the map block, stretching every column to `cell_width` cells.
+    fn parse_input(input: &Input, cell_width: i32) -> Self {
+        let grid = input
+            .grid(|c| matches!(c, 'O' | '#' | '@' | '.').then_some(c))
+            .expect("map input");
+        let scale = |pos: GridPos| {
+            let [x, y] = SparsePos::from(pos);
+            [cell_width * x, y]
+        };
+        let cells = grid
+            .enumerate()
+            .filter_map(|(pos, c)| match c {
+                'O' => Some((scale(pos), Cell::Box)),
+                '#' => Some((scale(pos), Cell::Wall)),
+                _ => None,
+            })
+            .collect();
         Self {
-            robot_pos: robot_pos.expect("No robot in map"),
+            robot_pos: scale(grid.find(&'@').expect("No robot in map")),
             cells,
             cell_width,
         }
     }
-    fn move_robot(&mut self, direction: Direction) -> Vec<[i16; 2]> {
-        let next_robot_pos = direction.step(self.robot_pos);
+
+    fn move_robot(&mut self, direction: Direction) -> Vec<SparsePos> {
+        let next_robot_pos = step(self.robot_pos, direction);
 
         let mut moved_box_positions = Vec::new();
         let mut collision_positions = vec![self.robot_pos];
@@ -102,7 +67,7 @@ impl Map {
                 .flat_map(|pos| {
                     let range_end = pos[0] + if first { 1 } else { self.cell_width };
                     (pos[0] - self.cell_width + 1..range_end)
-                        .map(move |x| direction.step([x, pos[1]]))
+                        .map(move |x| step([x, pos[1]], direction))
                 })
                 .collect();
             if potential_cell_positions
@@ -129,7 +94,7 @@ impl Map {
 
         let mut to_clear = Vec::new();
         for moved_box_position in moved_box_positions {
-            let next_position = direction.step(moved_box_position);
+            let next_position = step(moved_box_position, direction);
             to_clear.push(moved_box_position);
 
             self.cells.insert(next_position, Cell::Box);
@@ -143,9 +108,7 @@ impl Map {
     fn gps_score(&self) -> i32 {
         self.cells
             .iter()
-            .flat_map(|(pos, cell)| {
-                matches!(cell, Cell::Box).then_some((pos[0] + 100 * pos[1]) as i32)
-            })
+            .flat_map(|(pos, cell)| matches!(cell, Cell::Box).then_some(pos[0] + 100 * pos[1]))
             .sum()
     }
 
@@ -179,10 +142,11 @@ impl Map {
     }
 }
 
-pub fn move_boxes(input: &str, cell_width: i16) -> i32 {
+pub fn move_boxes(input: &str, cell_width: i32) -> i32 {
     let plot = std::env::var_os("PLOT").is_some();
-    let (map_input, direction_input) = input.split_once("\n\n").expect("separator of inputs");
-    let mut map = Map::parse_input(map_input, cell_width);
+    let input = Input::new(input);
+    let (map_input, direction_input) = input.blocks().collect_tuple().expect("map and directions");
+    let mut map = Map::parse_input(&map_input, cell_width);
     if plot {
         execute!(stdout(), Clear(ClearType::All)).expect("clear");
     }
@@ -1,12 +1,6 @@
-use core::panic;
 use std::{
-    cmp::Reverse,
-    collections::{
-        binary_heap::BinaryHeap, hash_map::Entry, BTreeMap, BTreeSet, HashMap, HashSet, VecDeque,
-    },
+    collections::{HashMap, HashSet},
     io::{self, stdout},
-    iter::repeat,
-    time::Duration,
 };
 
 use crossterm::{
@@ -16,204 +10,112 @@ use crossterm::{
     terminal::{Clear, ClearType, SetSize, WindowSize},
 };
 
-use crate::util::prelude::*;
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-enum Direction {
-    Up,
-    Right,
-    Down,
-    Left,
-}
-
-impl Direction {
-    fn opposite(&self) -> Self {
-        match self {
-            Direction::Up => Direction::Down,
-            Direction::Right => Direction::Left,
-            Direction::Down => Direction::Up,
-            Direction::Left => Direction::Right,
-        }
-    }
-}
-
-impl Direction {
-    fn step(&self, pos: [u16; 2]) -> [u16; 2] {
-        let delta = match self {
-            Direction::Up => [0, -1],
-            Direction::Right => [1, 0],
-            Direction::Down => [0, 1],
-            Direction::Left => [-1, 0],
-        };
-        [0, 1].map(|i| pos[i].checked_add_signed(delta[i]).expect("overflow"))
-    }
-}
+use crate::util::{
+    image::{Raster, Rgb},
+    prelude::*,
+    search::{dijkstra, ShortestPaths},
+    sparse::SparseGrid,
+};
 
-type Vertex = ([u16; 2], Direction);
+type Vertex = (GridPos, Direction);
 
 #[derive(Debug)]
 struct Map {
-    start_pos: [u16; 2],
-    end_pos: [u16; 2],
-    walls: HashSet<[u16; 2]>,
+    start_pos: GridPos,
+    end_pos: GridPos,
+    walls: SparseGrid<()>,
 }
 
 impl Map {
     fn parse_input(input: &str) -> Self {
-        let mut start_pos = None;
-        let mut end_pos = None;
-        let mut walls = HashSet::new();
-
-        for (y, line) in input.lines().enumerate() {
-            for (x, c) in line.chars().enumerate() {
-                let pos = [x as u16, y as u16];
-                match c {
-                    '#' => {
-                        walls.insert(pos);
-                    }
-                    'S' => {
-                        start_pos = Some(pos);
-                    }
-                    'E' => {
-                        end_pos = Some(pos);
-                    }
-                    '.' => {}
-                    c => panic!("Unexpected map input: {c}"),
-                }
-            }
-        }
+        let grid = Input::new(input)
+            .grid(|c| matches!(c, '#' | 'S' | 'E' | '.').then_some(c))
+            .expect("parse");
 
         Self {
-            start_pos: start_pos.expect("No start in map"),
-            end_pos: end_pos.expect("No end in map"),
-            walls,
+            start_pos: grid.find(&'S').expect("No start in map"),
+            end_pos: grid.find(&'E').expect("No end in map"),
+            walls: grid.find_all(&'#').map(|pos| (pos.into(), ())).collect(),
         }
     }
 
-    fn parents(&self) -> HashMap<Vertex, (usize, Option<Vertex>)> {
-        let mut queue = BinaryHeap::new();
-        queue.push((Reverse(0), self.start_pos, Direction::Right, None));
-        let mut parents = HashMap::new();
+    fn neighbors(&self, (pos, dir): &Vertex) -> impl Iterator<Item = (Vertex, usize)> {
+        let forward = pos
+            .step(*dir)
+            .filter(|next| !self.walls.contains(&(*next).into()))
+            .map(|next| ((next, *dir), 1));
+        let turns = [dir.turn_left(), dir.turn_right()].map(|turned| ((*pos, turned), 1000));
+        forward.into_iter().chain(turns)
+    }
+
+    fn shortest_paths(&self) -> ShortestPaths<Vertex, usize> {
+        dijkstra(
+            [(self.start_pos, Direction::Right)],
+            |vertex| self.neighbors(vertex),
+            |(pos, _)| *pos == self.end_pos,
+        )
+    }
+
+    /// The vertices on any best path together with their scores.
+    fn best_path_vertices(
+        &self,
+        paths: &ShortestPaths<Vertex, usize>,
+    ) -> (usize, Vec<(Vertex, usize)>) {
+        let total_score = paths.goal_distance().expect("end position not reachable");
+        let vertices = paths
+            .nodes_on_paths_to(paths.goals())
+            .into_iter()
+            .map(|vertex| (vertex, paths.distance(&vertex).expect("settled")))
+            .collect();
+        (total_score, vertices)
+    }
 
-        while let Some((Reverse(score), pos, direction, parent)) = queue.pop() {
-            let Entry::Vacant(e) = parents.entry((pos, direction)) else {
+    fn check_paths(&self, paths: &ShortestPaths<Vertex, usize>) {
+        for (vertex, score) in paths.distances() {
+            if *vertex == (self.start_pos, Direction::Right) {
+                assert_eq!(score, 0);
                 continue;
-            };
-            e.insert((score, parent));
-            if pos == self.end_pos {
-                break;
             }
-            for new_direction in [
-                Direction::Up,
-                Direction::Right,
-                Direction::Down,
-                Direction::Left,
-            ] {
-                if new_direction == direction.opposite() {
-                    continue;
-                }
-                let (new_pos, new_direction, score_delta) = if direction == new_direction {
-                    (direction.step(pos), direction, 1)
-                } else {
-                    (pos, new_direction, 1000)
-                };
-                if !parents.contains_key(&(new_pos, new_direction))
-                    && !self.walls.contains(&new_pos)
-                {
-                    queue.push((
-                        Reverse(score + score_delta),
-                        new_pos,
-                        new_direction,
-                        Some((pos, direction)),
-                    ))
-                }
+            assert!(!self.walls.contains(&vertex.0.into()));
+            let mut predecessors = paths.predecessors(vertex).peekable();
+            assert!(
+                predecessors.peek().is_some(),
+                "One neighbor must be visited"
+            );
+            for parent in predecessors {
+                let score_delta = if parent.0 == vertex.0 { 1000 } else { 1 };
+                assert_eq!(paths.distance(parent), Some(score - score_delta));
             }
         }
-        parents
     }
+}
 
-    fn check_parents(&self, parents: &HashMap<Vertex, (usize, Option<Vertex>)>) {
-        let width = self.walls.iter().copied().map(|pos| pos[0]).max().unwrap() + 1;
-        let height = self.walls.iter().copied().map(|pos| pos[1]).max().unwrap() + 1;
-
-        let directions = [
-            Direction::Up,
-            Direction::Right,
-            Direction::Down,
-            Direction::Left,
-        ];
-        for x in 0..width {
-            for y in 0..height {
-                for dir in directions {
-                    let key = ([x, y], dir);
-                    if key == (self.start_pos, Direction::Right) {
-                        assert_eq!(parents.get(&key).copied(), Some((0, None)));
-                        continue;
-                    }
-                    if self.walls.contains(&[x, y]) {
-                        assert!(!parents.contains_key(&([x, y], dir)));
-                        continue;
-                    }
-                    let mut neighbors = directions
-                        .into_iter()
-                        .filter(|new_dir| *new_dir != dir && *new_dir != dir.opposite())
-                        .map(|new_dir| ([x, y], new_dir, 1000))
-                        .collect_vec();
-                    neighbors.push((dir.opposite().step([x, y]), dir, 1));
-                    if let Some((score, _)) = parents.get(&([x, y], dir)).copied() {
-                        assert_eq!(
-                            score,
-                            neighbors
-                                .into_iter()
-                                .flat_map(|(new_pos, new_dir, score_delta)| {
-                                    let new_score = parents.get(&(new_pos, new_dir)).copied()?.0;
-                                    Some(new_score + score_delta)
-                                })
-                                .min()
-                                .expect("One neighbor must be visited")
-                        );
-                    };
-                }
-            }
-        }
+/// A channel value from 127 to 255 for `value` out of `max`. Values above `max` get the top
+/// value, and a zero `max` the bottom one.
+fn shade(value: usize, max: usize) -> u8 {
+    if max == 0 {
+        return 127;
     }
+    (127 + 128 * value.min(max) / max) as u8
 }
 
 fn score_to_color(score: usize, max_score: usize) -> Color {
-    let turns = score / 1000;
-    let steps = score % 1000;
-    let max_turns = max_score / 1000;
-    let max_steps = max_score % 1000;
-
     Color::Rgb {
-        r: (127 + (128 * turns) / max_turns) as u8,
-        g: (127 + (128 * steps) / max_steps) as u8,
+        r: shade(score / 1000, max_score / 1000),
+        g: shade(score % 1000, max_score % 1000),
         b: 127,
     }
 }
 
 pub fn level1_visualizer(input: &str, plot: bool) -> io::Result<usize> {
     let map = Map::parse_input(input);
-    let parents = map.parents();
+    let paths = map.shortest_paths();
 
     #[cfg(debug_assertions)]
-    map.check_parents(&parents);
-
-    let mut dir = [
-        Direction::Up,
-        Direction::Right,
-        Direction::Down,
-        Direction::Left,
-    ]
-    .into_iter()
-    .find(|direction| {
-        if let Some((_, Some((parent_pos, _)))) = parents.get(&(map.end_pos, *direction)) {
-            *parent_pos != map.end_pos
-        } else {
-            false
-        }
-    })
-    .expect("end position not reachable");
+    map.check_paths(&paths);
+
+    let total_score = paths.goal_distance().expect("end position not reachable");
+    let goal = *paths.goals().next().expect("end position not reachable");
 
     let mut stdout = stdout();
     let size = crossterm::terminal::size()?;
@@ -223,55 +125,34 @@ pub fn level1_visualizer(input: &str, plot: bool) -> io::Result<usize> {
             stdout,
             Clear(ClearType::All),
             Hide,
-            SetSize(
-                map.walls.iter().copied().map(|pos| pos[0]).max().unwrap(),
-                map.walls.iter().copied().map(|pos| pos[1]).max().unwrap(),
-            )
+            SetSize(map.walls.width() as u16, map.walls.height() as u16,)
         )?;
-        for pos in &map.walls {
+        for pos in map.walls.positions() {
             execute!(
                 stdout,
-                MoveTo(pos[0], pos[1]),
+                MoveTo(pos[0] as u16, pos[1] as u16),
                 PrintStyledContent('#'.grey())
             )?
         }
     }
 
-    let mut pos = map.end_pos;
-    let total_score = parents
-        .get(&(map.end_pos, dir))
-        .expect("end position not reachable")
-        .0;
     if plot {
-        let mut score = 0;
-        while let Some((current_score, Some((next_pos, next_dir)))) =
-            parents.get(&(pos, dir)).copied()
-        {
-            let c = match dir {
-                Direction::Up => '^',
-                Direction::Right => '>',
-                Direction::Down => 'v',
-                Direction::Left => '<',
-            };
+        let path = paths.path_to(&goal).expect("goal was settled");
+        for vertex in &path {
+            let (pos, dir) = *vertex;
+            let c: char = dir.into();
+            let score = paths.distance(vertex).expect("settled");
             execute!(
                 stdout,
-                MoveTo(pos[0], pos[1]),
-                PrintStyledContent(c.stylize().with(score_to_color(current_score, total_score)))
+                MoveTo(pos.x as u16, pos.y as u16),
+                PrintStyledContent(c.stylize().with(score_to_color(score, total_score)))
             )?;
-
-            debug_assert_eq!(total_score, score + current_score);
-            score += if pos == next_pos { 1000 } else { 1 };
-            pos = next_pos;
-            dir = next_dir;
         }
-        debug_assert_eq!((pos, dir), (map.start_pos, Direction::Right));
+        debug_assert_eq!(path.first(), Some(&(map.start_pos, Direction::Right)));
 
         execute!(
             stdout,
-            MoveTo(
-                0,
-                map.walls.iter().copied().map(|pos| pos[1]).max().unwrap() + 1,
-            ),
+            MoveTo(0, map.walls.height() as u16),
             Show,
             SetSize(size.0, size.1)
         )?;
@@ -285,7 +166,7 @@ pub fn level1(input: &str) -> usize {
 
 pub fn level2_visualizer(input: &str, plot: bool) -> io::Result<usize> {
     let map = Map::parse_input(input);
-    let parents = map.parents();
+    let paths = map.shortest_paths();
 
     let mut stdout = stdout();
     let size = crossterm::terminal::size()?;
@@ -295,89 +176,36 @@ pub fn level2_visualizer(input: &str, plot: bool) -> io::Result<usize> {
             stdout,
             Clear(ClearType::All),
             Hide,
-            SetSize(
-                map.walls.iter().copied().map(|pos| pos[0]).max().unwrap(),
-                map.walls.iter().copied().map(|pos| pos[1]).max().unwrap(),
-            )
+            SetSize(map.walls.width() as u16, map.walls.height() as u16,)
         )?;
-        for pos in &map.walls {
+        for pos in map.walls.positions() {
             execute!(
                 stdout,
-                MoveTo(pos[0], pos[1]),
+                MoveTo(pos[0] as u16, pos[1] as u16),
                 PrintStyledContent('#'.grey())
             )?
         }
     }
 
-    let dir = [
-        Direction::Up,
-        Direction::Right,
-        Direction::Down,
-        Direction::Left,
-    ]
-    .into_iter()
-    .find(|direction| {
-        if let Some((_, Some((parent_pos, _)))) = parents.get(&(map.end_pos, *direction)) {
-            *parent_pos != map.end_pos
-        } else {
-            false
-        }
-    })
-    .expect("end position not reachable");
-    let total_score = parents
-        .get(&(map.end_pos, dir))
-        .expect("end position not reachable")
-        .0;
-
-    let mut visited = HashSet::new();
-    let mut queue = VecDeque::new();
-    queue.push_back((map.end_pos, dir));
-
-    while let Some((pos, dir)) = queue.pop_front() {
-        visited.insert(pos);
-        let current_score = parents.get(&(pos, dir)).expect("was visited").0;
-        let c = match dir {
-            Direction::Up => '^',
-            Direction::Right => '>',
-            Direction::Down => 'v',
-            Direction::Left => '<',
-        };
-        if plot {
+    let (total_score, vertices) = map.best_path_vertices(&paths);
+    if plot {
+        for ((pos, dir), current_score) in &vertices {
+            let c: char = (*dir).into();
             execute!(
                 stdout,
-                MoveTo(pos[0], pos[1]),
-                PrintStyledContent(c.stylize().with(score_to_color(current_score, total_score)))
+                MoveTo(pos.x as u16, pos.y as u16),
+                PrintStyledContent(
+                    c.stylize()
+                        .with(score_to_color(*current_score, total_score))
+                )
             )?;
         }
-        let directions = [
-            Direction::Up,
-            Direction::Right,
-            Direction::Down,
-            Direction::Left,
-        ];
-        let mut neighbors = directions
-            .into_iter()
-            .filter(|new_dir| *new_dir != dir && *new_dir != dir.opposite())
-            .map(|new_dir| (pos, new_dir, 1000))
-            .collect_vec();
-        neighbors.push((dir.opposite().step(pos), dir, 1));
-
-        queue.extend(
-            neighbors
-                .into_iter()
-                .flat_map(|(new_pos, new_dir, score_delta)| {
-                    let new_score = parents.get(&(new_pos, new_dir))?.0;
-                    (new_score + score_delta == current_score).then_some((new_pos, new_dir))
-                }),
-        );
     }
+    let visited: HashSet<_> = vertices.into_iter().map(|((pos, _), _)| pos).collect();
     if plot {
         execute!(
             stdout,
-            MoveTo(
-                0,
-                map.walls.iter().copied().map(|pos| pos[1]).max().unwrap() + 1,
-            ),
+            MoveTo(0, map.walls.height() as u16),
             Show,
             SetSize(size.0, size.1)
         )?;
@@ -389,10 +217,54 @@ pub fn level2(input: &str) -> usize {
     level2_visualizer(input, std::env::var_os("PLOT").is_some()).expect("plot")
 }
 
+/// The map with walls in grey and the tiles on any best path coloured by their score.
+pub fn best_tiles_image(input: &str) -> Raster {
+    let map = Map::parse_input(input);
+    let paths = map.shortest_paths();
+    let (total_score, vertices) = map.best_path_vertices(&paths);
+    let scores: HashMap<_, _> = vertices
+        .into_iter()
+        .map(|((pos, _), score)| (pos, score))
+        .collect();
+    Raster::from_cells(map.walls.width(), map.walls.height(), 4, |pos| {
+        if map.walls.contains(&pos.into()) {
+            Rgb::GREY
+        } else if let Some(score) = scores.get(&pos) {
+            score_to_color(*score, total_score).into()
+        } else {
+            Rgb::WHITE
+        }
+    })
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
 
+    #[test]
+    fn score_colors() {
+        let rgb = |score, max_score| match score_to_color(score, max_score) {
+            Color::Rgb { r, g, b } => [r, g, b],
+            _ => unreachable!(),
+        };
+        assert_eq!(rgb(0, 0), [127, 127, 127]);
+        assert_eq!(rgb(5, 10), [127, 191, 127]);
+        assert_eq!(rgb(1000, 2000), [191, 127, 127]);
+        // More steps than the final score has, since turns are worth more.
+        assert_eq!(rgb(1999, 2000), [191, 127, 127]);
+        assert_eq!(rgb(1999, 3005), [169, 255, 127]);
+    }
+
+    #[test]
+    fn best_tiles_raster() {
+        let test_input = include_str!("./test_input/day16.txt");
+        let raster = best_tiles_image(test_input);
+        assert_eq!((raster.width(), raster.height()), (60, 60));
+        assert_eq!(raster.get(0, 0), Some(Rgb::GREY));
+        let start = raster.get(4, 13 * 4).unwrap();
+        assert!(start != Rgb::GREY && start != Rgb::WHITE);
+    }
+
     #[test]
     fn level1_given_example_first() {
         let test_input = include_str!("./test_input/day16.txt");
@@ -1,61 +1,49 @@
-use core::panic;
+use std::fmt::Display;
 
 use crate::util::prelude::*;
 
 #[derive(Debug, Clone)]
-struct Computer {
+pub struct Computer {
     registers: [u64; 3],
     instructions: Vec<u8>,
     eip: usize,
 }
 
 impl Computer {
-    fn parse_input(input: &str) -> Result<Self, &'static str> {
-        let mut lines = input.lines();
-        let mut registers = [0; 3];
-        for register in &mut registers {
-            let line = lines.next().ok_or("too few registers")?;
-            let (_, value) = line.split_once(": ").ok_or("unexpected line")?;
-            *register = value.parse().map_err(|_| "not a number")?;
-        }
-        lines.next().expect("empty line separator");
-        let (_, instructions_text) = lines
-            .next()
-            .ok_or("no instructions")?
-            .split_once(": ")
-            .ok_or("unexpected line")?;
-        let instructions: Result<Vec<u8>, _> = instructions_text
-            .split(',')
-            .map(|x| -> Result<u8, _> { x.parse().map_err(|_| "not a number") })
-            .collect();
+    pub fn parse_input(input: &str) -> Result<Self, &'static str> {
+        let input = Input::new(input);
+        let (register_block, program_block) = input
+            .blocks()
+            .collect_tuple()
+            .ok_or("expected registers and program")?;
+        let registers: Vec<u64> = register_block
+            .ints()
+            .map(|line| match line[..] {
+                [value] => value.try_into().map_err(|_| "negative register"),
+                _ => Err("unexpected line"),
+            })
+            .try_collect()?;
+        let instructions: Vec<u8> = program_block
+            .ints()
+            .exactly_one()
+            .map_err(|_| "unexpected line")?
+            .into_iter()
+            .map(|x| x.try_into().map_err(|_| "not a number"))
+            .try_collect()?;
         Ok(Self {
-            registers,
-            instructions: instructions?,
+            registers: registers.try_into().map_err(|_| "too few registers")?,
+            instructions,
             eip: 0,
         })
     }
 
-    fn get_first_output_bit(&self, register_a: u64) -> u8 {
-        let cutoff = self.instructions.len() - 6;
-        let mut iter = ComputerIterator {
-            registers: [register_a, 0, 0],
+    /// A computer running an assembled program, see [`assemble`].
+    pub fn assembled(source: &str, registers: [u64; 3]) -> Result<Self, AssemblyError> {
+        Ok(Self {
+            registers,
+            instructions: assemble(source)?,
             eip: 0,
-            instructions: &self.instructions[..cutoff],
-        };
-        assert!(iter.next().is_none());
-        (iter.registers[1] & 7) as u8
-    }
-
-    fn find_fixed_point(&self) -> Option<u64> {
-        let mut candidates = vec![0];
-        for instruction in self.instructions.iter().rev() {
-            candidates = candidates
-                .into_iter()
-                .flat_map(|candidate| (0..8).map(move |byte| (candidate << 3) + byte as u64))
-                .filter(|candidate| self.get_first_output_bit(*candidate) == *instruction)
-                .collect()
-        }
-        candidates.into_iter().min()
+        })
     }
 }
 
@@ -73,8 +61,239 @@ impl<'a> IntoIterator for &'a Computer {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Opcode {
+    Adv,
+    Bxl,
+    Bst,
+    Jnz,
+    Bxc,
+    Out,
+    Bdv,
+    Cdv,
+}
+
+impl Opcode {
+    const ALL: [Self; 8] = [
+        Self::Adv,
+        Self::Bxl,
+        Self::Bst,
+        Self::Jnz,
+        Self::Bxc,
+        Self::Out,
+        Self::Bdv,
+        Self::Cdv,
+    ];
+
+    fn mnemonic(self) -> &'static str {
+        match self {
+            Opcode::Adv => "adv",
+            Opcode::Bxl => "bxl",
+            Opcode::Bst => "bst",
+            Opcode::Jnz => "jnz",
+            Opcode::Bxc => "bxc",
+            Opcode::Out => "out",
+            Opcode::Bdv => "bdv",
+            Opcode::Cdv => "cdv",
+        }
+    }
+
+    /// Whether the operand is a combo operand rather than a literal.
+    fn takes_combo(self) -> bool {
+        matches!(
+            self,
+            Opcode::Adv | Opcode::Bst | Opcode::Out | Opcode::Bdv | Opcode::Cdv
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Instruction {
+    opcode: Opcode,
+    operand: u8,
+}
+
+impl Instruction {
+    fn decode(opcode: u8, operand: u8) -> Option<Self> {
+        Some(Self {
+            opcode: *Opcode::ALL.get(opcode as usize)?,
+            operand,
+        })
+    }
+
+    /// Combo operand 7 is reserved and must not appear in valid programs.
+    fn uses_reserved_operand(&self) -> bool {
+        self.opcode.takes_combo() && self.operand >= 7
+    }
+
+    fn operand_text(&self) -> String {
+        match (self.opcode.takes_combo(), self.operand) {
+            (true, 4) => "a".to_string(),
+            (true, 5) => "b".to_string(),
+            (true, 6) => "c".to_string(),
+            (_, operand) => operand.to_string(),
+        }
+    }
+
+    /// What the instruction does, written as an assignment.
+    fn pseudo_code(&self) -> String {
+        let operand = self.operand_text();
+        match self.opcode {
+            Opcode::Adv => format!("a = a >> {operand}"),
+            Opcode::Bxl => format!("b = b ^ {operand}"),
+            Opcode::Bst => format!("b = {operand} % 8"),
+            Opcode::Jnz => format!("if a != 0: jump {operand}"),
+            Opcode::Bxc => "b = b ^ c".to_string(),
+            Opcode::Out => format!("output {operand} % 8"),
+            Opcode::Bdv => format!("b = a >> {operand}"),
+            Opcode::Cdv => format!("c = a >> {operand}"),
+        }
+    }
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.opcode.mnemonic(), self.operand_text())
+    }
+}
+
+/// A listing of the program with addresses, decoded operands and their meaning.
+pub fn disassemble(instructions: &[u8]) -> String {
+    let mut listing = String::new();
+    for (address, chunk) in instructions.chunks(2).enumerate() {
+        let address = 2 * address;
+        let line = match chunk {
+            [opcode, operand] => match Instruction::decode(*opcode, *operand) {
+                Some(instruction) => {
+                    let note = if instruction.uses_reserved_operand() {
+                        "reserved operand".to_string()
+                    } else {
+                        instruction.pseudo_code()
+                    };
+                    let instruction = instruction.to_string();
+                    format!("{address:>3}  {instruction:<6} ; {note}")
+                }
+                None => format!("{address:>3}  ??? {operand} ; invalid opcode {opcode}"),
+            },
+            [opcode] => format!("{address:>3}  ??? ; opcode {opcode} without operand"),
+            _ => unreachable!("chunks of two"),
+        };
+        listing.push_str(line.trim_end());
+        listing.push('\n');
+    }
+    listing
+}
+
+/// The plain assembly text of a program, one instruction per line, or `None` if some bytes
+/// don't form an instruction.
+pub fn to_assembly(instructions: &[u8]) -> Option<String> {
+    instructions
+        .chunks(2)
+        .map(|chunk| match chunk {
+            [opcode, operand] => Instruction::decode(*opcode, *operand),
+            _ => None,
+        })
+        .map(|instruction| Some(format!("{}\n", instruction?)))
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssemblyErrorKind {
+    UnknownMnemonic(String),
+    MissingOperand,
+    InvalidOperand(String),
+    TrailingText(String),
+    AddressMismatch { expected: usize, found: usize },
+}
+
+/// An assembly error on a one-based source line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssemblyError {
+    pub line: usize,
+    pub kind: AssemblyErrorKind,
+}
+
+impl Display for AssemblyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            AssemblyErrorKind::UnknownMnemonic(mnemonic) => {
+                write!(f, "unknown instruction {mnemonic:?}")
+            }
+            AssemblyErrorKind::MissingOperand => write!(f, "missing operand"),
+            AssemblyErrorKind::InvalidOperand(operand) => write!(f, "invalid operand {operand:?}"),
+            AssemblyErrorKind::TrailingText(text) => write!(f, "unexpected {text:?}"),
+            AssemblyErrorKind::AddressMismatch { expected, found } => {
+                write!(f, "address {found} should be {expected}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AssemblyError {}
+
+/// Assembles text like `adv 3` / `out a` / `jnz 0` into opcodes and operands. Combo operands
+/// may name a register, `bxc` may omit its ignored operand, and `;` starts a comment. Lines may
+/// start with their address, so [`disassemble`] listings of valid programs assemble as well.
+pub fn assemble(source: &str) -> Result<Vec<u8>, AssemblyError> {
+    let mut instructions = Vec::new();
+    for (index, line) in source.lines().enumerate() {
+        let error = |kind| AssemblyError {
+            line: index + 1,
+            kind,
+        };
+        let code = line.split(';').next().unwrap_or_default();
+        let mut tokens = code.split_whitespace().peekable();
+        if let Some(address) =
+            tokens.next_if(|token| token.starts_with(|c: char| c.is_ascii_digit()))
+        {
+            let expected = instructions.len();
+            match address.parse() {
+                Ok(found) if found == expected => {}
+                Ok(found) => {
+                    return Err(error(AssemblyErrorKind::AddressMismatch {
+                        expected,
+                        found,
+                    }))
+                }
+                Err(_) => {
+                    return Err(error(AssemblyErrorKind::UnknownMnemonic(
+                        address.to_string(),
+                    )))
+                }
+            }
+        }
+        let Some(mnemonic) = tokens.next() else {
+            continue;
+        };
+        let opcode = Opcode::ALL
+            .into_iter()
+            .find(|opcode| opcode.mnemonic() == mnemonic)
+            .ok_or_else(|| error(AssemblyErrorKind::UnknownMnemonic(mnemonic.to_string())))?;
+        let operand = match (tokens.next(), opcode) {
+            (None, Opcode::Bxc) => 0,
+            (None, _) => return Err(error(AssemblyErrorKind::MissingOperand)),
+            (Some(operand), _) => match (operand, opcode.takes_combo()) {
+                ("a", true) => 4,
+                ("b", true) => 5,
+                ("c", true) => 6,
+                (number, _) => number
+                    .parse()
+                    .ok()
+                    .filter(|number| *number < 8)
+                    .ok_or_else(|| error(AssemblyErrorKind::InvalidOperand(number.to_string())))?,
+            },
+        };
+        if let Some(extra) = tokens.next() {
+            return Err(error(AssemblyErrorKind::TrailingText(extra.to_string())));
+        }
+        instructions.extend([opcode as u8, operand]);
+    }
+    Ok(instructions)
+}
+
 #[derive(Debug, Clone)]
-struct ComputerIterator<'a> {
+pub struct ComputerIterator<'a> {
     registers: [u64; 3],
     instructions: &'a [u8],
     eip: usize,
@@ -89,98 +308,449 @@ impl ComputerIterator<'_> {
         }
     }
 
-    fn adv(&mut self, input: u8) {
-        let input = self.combo_value(input);
-        self.registers[0] >>= input;
-        self.eip += 2;
+    /// `a` divided by two to the power of the combo operand.
+    fn divide(&self, operand: u8) -> u64 {
+        let shift = self.combo_value(operand);
+        u32::try_from(shift)
+            .ok()
+            .and_then(|shift| self.registers[0].checked_shr(shift))
+            .unwrap_or(0)
     }
 
-    fn bxl(&mut self, input: u8) {
-        self.registers[1] ^= input as u64;
-        self.eip += 2;
+    /// The instruction at the instruction pointer, or `None` if the program halts there.
+    fn fetch(&self) -> Option<Result<Instruction, ExecutionError>> {
+        let opcode = *self.instructions.get(self.eip)?;
+        let operand = *self.instructions.get(self.eip + 1)?;
+        let eip = self.eip;
+        Some(match Instruction::decode(opcode, operand) {
+            None => Err(ExecutionError::InvalidOpcode { eip, opcode }),
+            Some(instruction) if instruction.uses_reserved_operand() => {
+                Err(ExecutionError::ReservedOperand { eip })
+            }
+            Some(instruction) => Ok(instruction),
+        })
     }
 
-    fn bst(&mut self, input: u8) {
-        self.registers[1] = self.combo_value(input) & 0b111;
+    fn execute(&mut self, instruction: Instruction) -> Option<u8> {
+        let Instruction { opcode, operand } = instruction;
+        let mut output = None;
+        match opcode {
+            Opcode::Adv => self.registers[0] = self.divide(operand),
+            Opcode::Bxl => self.registers[1] ^= operand as u64,
+            Opcode::Bst => self.registers[1] = self.combo_value(operand) & 0b111,
+            Opcode::Jnz if self.registers[0] != 0 => {
+                self.eip = operand as usize;
+                return None;
+            }
+            Opcode::Jnz => {}
+            Opcode::Bxc => self.registers[1] ^= self.registers[2],
+            Opcode::Out => output = Some((self.combo_value(operand) & 0b111) as u8),
+            Opcode::Bdv => self.registers[1] = self.divide(operand),
+            Opcode::Cdv => self.registers[2] = self.divide(operand),
+        }
         self.eip += 2;
+        output
     }
 
-    fn jnz(&mut self, input: u8) {
-        if self.registers[0] == 0 {
-            self.eip += 2;
-        } else {
-            self.eip = input as usize
+    fn step(&mut self) -> Option<Option<u8>> {
+        let instruction = self.fetch()?.unwrap_or_else(|e| panic!("{e}"));
+        Some(self.execute(instruction))
+    }
+}
+
+impl Iterator for ComputerIterator<'_> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(output) = self.step()? {
+                break Some(output);
+            }
         }
     }
+}
 
-    fn bxc(&mut self, _input: u8) {
-        self.registers[1] ^= self.registers[2];
-        self.eip += 2;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecutionError {
+    InvalidOpcode { eip: usize, opcode: u8 },
+    ReservedOperand { eip: usize },
+    StepLimit { limit: usize },
+}
+
+impl Display for ExecutionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExecutionError::InvalidOpcode { eip, opcode } => {
+                write!(f, "invalid opcode {opcode} at {eip}")
+            }
+            ExecutionError::ReservedOperand { eip } => {
+                write!(f, "reserved combo operand 7 at {eip}")
+            }
+            ExecutionError::StepLimit { limit } => {
+                write!(f, "no halt within {limit} steps")
+            }
+        }
     }
+}
 
-    fn out(&mut self, input: u8) -> u8 {
-        self.eip += 2;
-        (self.combo_value(input) & 0b111) as u8
+impl std::error::Error for ExecutionError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Breakpoint {
+    /// Before executing the instruction at this address.
+    Address(usize),
+    /// After any output.
+    Output,
+    /// After outputting this value.
+    OutputValue(u8),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stop {
+    Halted,
+    Breakpoint(Breakpoint),
+}
+
+/// The machine state before one executed instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceEntry {
+    pub eip: usize,
+    pub registers: [u64; 3],
+    pub instruction: Instruction,
+}
+
+impl Display for TraceEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let [a, b, c] = self.registers;
+        let instruction = self.instruction.to_string();
+        write!(f, "{:>3}  {instruction:<6} a={a} b={b} c={c}", self.eip)
+    }
+}
+
+/// Runs a program step by step, with optional tracing, breakpoints and a step limit.
+#[derive(Debug, Clone)]
+pub struct Debugger<'a> {
+    state: ComputerIterator<'a>,
+    breakpoints: Vec<Breakpoint>,
+    step_limit: Option<usize>,
+    steps: usize,
+    trace: Option<Vec<TraceEntry>>,
+    output: Vec<u8>,
+    /// The address of the last stop if it was at an address breakpoint, which the next run
+    /// must not stop at again before executing that instruction.
+    stopped_at: Option<usize>,
+}
+
+impl<'a> Debugger<'a> {
+    pub fn new(computer: &'a Computer) -> Self {
+        Self {
+            state: computer.into_iter(),
+            breakpoints: Vec::new(),
+            step_limit: None,
+            steps: 0,
+            trace: None,
+            output: Vec::new(),
+            stopped_at: None,
+        }
     }
 
-    fn bdv(&mut self, input: u8) {
-        self.registers[1] = self.registers[0] >> self.combo_value(input);
-        self.eip += 2
+    pub fn with_breakpoint(mut self, breakpoint: Breakpoint) -> Self {
+        self.breakpoints.push(breakpoint);
+        self
     }
 
-    fn cdv(&mut self, input: u8) {
-        self.registers[2] = self.registers[0] >> self.combo_value(input);
-        self.eip += 2
+    /// Fails with [`ExecutionError::StepLimit`] instead of executing more than `limit`
+    /// instructions in total.
+    pub fn with_step_limit(mut self, limit: usize) -> Self {
+        self.step_limit = Some(limit);
+        self
     }
 
-    fn step(&mut self) -> Option<Option<u8>> {
-        let opcode = self.instructions.get(self.eip)?;
-        let operand = *self.instructions.get(self.eip + 1)?;
-        Some(match opcode {
-            0 => {
-                self.adv(operand);
-                None
+    /// Records the state before every instruction.
+    pub fn with_trace(mut self) -> Self {
+        self.trace = Some(Vec::new());
+        self
+    }
+
+    pub fn trace(&self) -> &[TraceEntry] {
+        self.trace.as_deref().unwrap_or_default()
+    }
+
+    pub fn output(&self) -> &[u8] {
+        &self.output
+    }
+
+    pub fn registers(&self) -> [u64; 3] {
+        self.state.registers
+    }
+
+    pub fn eip(&self) -> usize {
+        self.state.eip
+    }
+
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// Executes until the program halts or hits a breakpoint. Calling it again resumes
+    /// after the breakpoint.
+    pub fn run(&mut self) -> Result<Stop, ExecutionError> {
+        let mut resuming_at = self.stopped_at.take();
+        loop {
+            let Some(instruction) = self.state.fetch() else {
+                return Ok(Stop::Halted);
+            };
+            let instruction = instruction?;
+            let eip = self.state.eip;
+            if resuming_at.take() != Some(eip)
+                && self.breakpoints.contains(&Breakpoint::Address(eip))
+            {
+                self.stopped_at = Some(eip);
+                return Ok(Stop::Breakpoint(Breakpoint::Address(eip)));
             }
-            1 => {
-                self.bxl(operand);
-                None
+            if let Some(limit) = self.step_limit.filter(|limit| self.steps >= *limit) {
+                return Err(ExecutionError::StepLimit { limit });
             }
-            2 => {
-                self.bst(operand);
-                None
+            if let Some(trace) = &mut self.trace {
+                trace.push(TraceEntry {
+                    eip,
+                    registers: self.state.registers,
+                    instruction,
+                });
             }
-            3 => {
-                self.jnz(operand);
-                None
+            self.steps += 1;
+            if let Some(value) = self.state.execute(instruction) {
+                self.output.push(value);
+                for breakpoint in [Breakpoint::Output, Breakpoint::OutputValue(value)] {
+                    if self.breakpoints.contains(&breakpoint) {
+                        return Ok(Stop::Breakpoint(breakpoint));
+                    }
+                }
             }
-            4 => {
-                self.bxc(operand);
-                None
+        }
+    }
+}
+
+/// Why a program doesn't fit the shape the backwards quine search relies on: a single loop over
+/// the whole program that consumes a fixed number of bits of `a` and outputs one value per
+/// iteration, computed from `a` alone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ShapeError {
+    Invalid(ExecutionError),
+    OddLength,
+    NoJump,
+    JumpNotLast { eip: usize },
+    JumpTarget { target: u8 },
+    Outputs { count: usize },
+    Shifts { count: usize },
+    ShiftOperand { eip: usize },
+    CarriedRegister { register: char, eip: usize },
+}
+
+impl Display for ShapeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShapeError::Invalid(error) => write!(f, "is invalid: {error}"),
+            ShapeError::OddLength => write!(f, "has an odd length"),
+            ShapeError::NoJump => write!(f, "has no jnz"),
+            ShapeError::JumpNotLast { eip } => write!(f, "has a jnz at {eip} before the end"),
+            ShapeError::JumpTarget { target } => {
+                write!(f, "loops back to {target} instead of 0")
             }
-            5 => Some(self.out(operand)),
-            6 => {
-                self.bdv(operand);
-                None
+            ShapeError::Outputs { count } => {
+                write!(f, "outputs {count} values per iteration instead of one")
             }
-            7 => {
-                self.cdv(operand);
-                None
+            ShapeError::Shifts { count } => {
+                write!(f, "shifts a {count} times per iteration instead of once")
             }
-            _ => panic!("Unexpected opcode"),
-        })
+            ShapeError::ShiftOperand { eip } => {
+                write!(f, "shifts a by something other than 1 to 3 bits at {eip}")
+            }
+            ShapeError::CarriedRegister { register, eip } => {
+                write!(
+                    f,
+                    "reads {register} at {eip} before setting it in the iteration"
+                )
+            }
+        }
     }
 }
 
-impl Iterator for ComputerIterator<'_> {
-    type Item = u8;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum QuineError {
+    /// The program has the expected shape, but no value of `a` reproduces it.
+    NoSolution,
+    /// The program doesn't have the expected shape, and a brute force search found nothing.
+    NotFound { shape: ShapeError, searched: u64 },
+}
 
-    fn next(&mut self) -> Option<Self::Item> {
+impl Display for QuineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QuineError::NoSolution => write!(f, "no value of a reproduces the program"),
+            QuineError::NotFound { shape, searched } => write!(
+                f,
+                "the program {shape}, and no value of a up to {searched} reproduces it"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for QuineError {}
+
+impl Computer {
+    /// Checks the program's shape and returns the number of bits of `a` consumed per iteration.
+    fn loop_shift(&self) -> Result<u32, ShapeError> {
+        if !self.instructions.len().is_multiple_of(2) {
+            return Err(ShapeError::OddLength);
+        }
+        let body = self
+            .instructions
+            .chunks(2)
+            .enumerate()
+            .map(|(i, chunk)| {
+                let eip = 2 * i;
+                match Instruction::decode(chunk[0], chunk[1]) {
+                    None => Err(ExecutionError::InvalidOpcode {
+                        eip,
+                        opcode: chunk[0],
+                    }),
+                    Some(instruction) if instruction.uses_reserved_operand() => {
+                        Err(ExecutionError::ReservedOperand { eip })
+                    }
+                    Some(instruction) => Ok((eip, instruction)),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(ShapeError::Invalid)?;
+
+        let Some((_, last)) = body.last().filter(|(_, last)| last.opcode == Opcode::Jnz) else {
+            return Err(match body.iter().find(|(_, i)| i.opcode == Opcode::Jnz) {
+                Some((eip, _)) => ShapeError::JumpNotLast { eip: *eip },
+                None => ShapeError::NoJump,
+            });
+        };
+        if last.operand != 0 {
+            return Err(ShapeError::JumpTarget {
+                target: last.operand,
+            });
+        }
+        let body = &body[..body.len() - 1];
+        if let Some((eip, _)) = body.iter().find(|(_, i)| i.opcode == Opcode::Jnz) {
+            return Err(ShapeError::JumpNotLast { eip: *eip });
+        }
+
+        let count = |opcode| body.iter().filter(|(_, i)| i.opcode == opcode).count();
+        if count(Opcode::Out) != 1 {
+            return Err(ShapeError::Outputs {
+                count: count(Opcode::Out),
+            });
+        }
+        let shifts = body
+            .iter()
+            .filter(|(_, i)| i.opcode == Opcode::Adv)
+            .collect_vec();
+        let [(eip, shift)] = shifts[..] else {
+            return Err(ShapeError::Shifts {
+                count: shifts.len(),
+            });
+        };
+        if !(1..4).contains(&shift.operand) {
+            return Err(ShapeError::ShiftOperand { eip: *eip });
+        }
+
+        // `b` and `c` have to be overwritten before use, so each iteration only depends on `a`.
+        let mut written = [true, false, false];
+        for (eip, instruction) in body {
+            let combo = instruction.opcode.takes_combo() && (4..7).contains(&instruction.operand);
+            let reads = [
+                false,
+                matches!(instruction.opcode, Opcode::Bxl | Opcode::Bxc)
+                    || combo && instruction.operand == 5,
+                instruction.opcode == Opcode::Bxc || combo && instruction.operand == 6,
+            ];
+            if let Some(register) = (0..3).find(|r| reads[*r] && !written[*r]) {
+                return Err(ShapeError::CarriedRegister {
+                    register: ['a', 'b', 'c'][register],
+                    eip: *eip,
+                });
+            }
+            match instruction.opcode {
+                Opcode::Bxl | Opcode::Bst | Opcode::Bxc | Opcode::Bdv => written[1] = true,
+                Opcode::Cdv => written[2] = true,
+                _ => {}
+            }
+        }
+        Ok(shift.operand as u32)
+    }
+
+    /// The single value output by one pass through the loop body, starting from `a`.
+    fn iteration_output(&self, a: u64) -> u8 {
+        let mut body = ComputerIterator {
+            registers: [a, 0, 0],
+            instructions: &self.instructions[..self.instructions.len() - 2],
+            eip: 0,
+        };
+        body.next().expect("checked by loop_shift")
+    }
+
+    /// Whether the program outputs itself when started with `a`.
+    fn is_quine(&self, a: u64) -> bool {
+        let computer = Computer {
+            registers: [a, 0, 0],
+            ..self.clone()
+        };
+        let mut debugger = Debugger::new(&computer)
+            .with_breakpoint(Breakpoint::Output)
+            .with_step_limit(100 * self.instructions.len() + 1000);
         loop {
-            if let Some(output) = self.step()? {
-                break Some(output);
+            match debugger.run() {
+                Ok(Stop::Halted) => return debugger.output() == self.instructions,
+                Ok(Stop::Breakpoint(_)) if self.instructions.starts_with(debugger.output()) => {}
+                _ => return false,
             }
         }
     }
+
+    /// Builds `a` from the last output to the first, a few bits per iteration.
+    fn quine_backwards(&self, shift: u32) -> Option<u64> {
+        let mut candidates = vec![0u64];
+        for expected in self.instructions.iter().rev() {
+            candidates = candidates
+                .into_iter()
+                .filter(|candidate| candidate.leading_zeros() >= shift)
+                .flat_map(|candidate| (0..1 << shift).map(move |bits| (candidate << shift) | bits))
+                // The loop ends as soon as `a` becomes zero, so it never is at the start.
+                .filter(|a| *a != 0 && self.iteration_output(*a) == *expected)
+                .collect();
+        }
+        candidates.sort_unstable();
+        candidates.into_iter().find(|a| self.is_quine(*a))
+    }
+
+    fn quine_bounded(&self, limit: u64) -> Option<u64> {
+        (1..=limit).find(|a| self.is_quine(*a))
+    }
+
+    /// The lowest positive initial `a` for which the program outputs itself. Programs of the
+    /// usual shape are solved backwards, anything else by trying values up to `search_limit`.
+    fn find_quine(&self, search_limit: u64) -> Result<u64, QuineError> {
+        match self.loop_shift() {
+            Ok(shift) => self.quine_backwards(shift).ok_or(QuineError::NoSolution),
+            Err(shape) => self
+                .quine_bounded(search_limit)
+                .ok_or(QuineError::NotFound {
+                    shape,
+                    searched: search_limit,
+                }),
+        }
+    }
+}
+
+/// The program's listing, see [`disassemble`].
+pub fn listing(input: &str) -> String {
+    let computer = Computer::parse_input(input).expect("parse");
+    disassemble(&computer.instructions)
 }
 
 pub fn level1(input: &str) -> String {
@@ -191,13 +761,15 @@ pub fn level1(input: &str) -> String {
 pub fn level2(input: &str) -> String {
     let computer = Computer::parse_input(input).expect("parse");
     computer
-        .find_fixed_point()
-        .expect("no fixed point")
+        .find_quine(1 << 20)
+        .unwrap_or_else(|e| panic!("{e}"))
         .to_string()
 }
 
 #[cfg(test)]
 mod test {
+    use std::iter::successors;
+
     use super::*;
 
     #[test]
@@ -211,4 +783,276 @@ mod test {
         let test_input = include_str!("./test_input/day17.txt");
         assert_eq!(&level1(test_input), "4,6,3,5,6,3,5,2,1,0")
     }
+
+    #[test]
+    fn disassembly() {
+        let listing = disassemble(&[2, 4, 1, 1, 7, 5, 4, 6, 5, 5, 0, 7, 3, 0, 9]);
+        assert_eq!(
+            listing,
+            "  0  bst a  ; b = a % 8\n\
+             \x20 2  bxl 1  ; b = b ^ 1\n\
+             \x20 4  cdv b  ; c = a >> b\n\
+             \x20 6  bxc 6  ; b = b ^ c\n\
+             \x20 8  out b  ; output b % 8\n\
+             \x2010  adv 7  ; reserved operand\n\
+             \x2012  jnz 0  ; if a != 0: jump 0\n\
+             \x2014  ??? ; opcode 9 without operand\n"
+        );
+    }
+
+    #[test]
+    fn trace_and_breakpoints() {
+        let computer = Computer::parse_input(include_str!("./test_input/day17.txt")).unwrap();
+        let mut debugger = Debugger::new(&computer)
+            .with_trace()
+            .with_breakpoint(Breakpoint::OutputValue(3))
+            .with_breakpoint(Breakpoint::Address(4));
+        assert_eq!(debugger.run(), Ok(Stop::Breakpoint(Breakpoint::Address(4))));
+        assert_eq!(debugger.trace()[1].to_string(), "  2  out a  a=364 b=0 c=0");
+        assert_eq!(debugger.run(), Ok(Stop::Breakpoint(Breakpoint::Address(4))));
+        assert_eq!(debugger.output(), [4, 6]);
+        assert_eq!(
+            debugger.run(),
+            Ok(Stop::Breakpoint(Breakpoint::OutputValue(3)))
+        );
+        assert_eq!(debugger.output(), [4, 6, 3]);
+        assert_eq!(debugger.registers(), [91, 0, 0]);
+
+        // A breakpoint on the first instruction stops before executing anything.
+        let mut debugger = Debugger::new(&computer).with_breakpoint(Breakpoint::Address(0));
+        assert_eq!(debugger.run(), Ok(Stop::Breakpoint(Breakpoint::Address(0))));
+        assert_eq!(debugger.steps(), 0);
+        assert_eq!(debugger.run(), Ok(Stop::Breakpoint(Breakpoint::Address(0))));
+        assert_eq!(debugger.steps(), 3);
+
+        // Resuming after an output still stops at an address breakpoint right after it.
+        let mut debugger = Debugger::new(&computer)
+            .with_breakpoint(Breakpoint::Output)
+            .with_breakpoint(Breakpoint::Address(4));
+        assert_eq!(debugger.run(), Ok(Stop::Breakpoint(Breakpoint::Output)));
+        assert_eq!(debugger.eip(), 4);
+        assert_eq!(debugger.run(), Ok(Stop::Breakpoint(Breakpoint::Address(4))));
+        assert_eq!(debugger.steps(), 2);
+
+        let mut debugger = Debugger::new(&computer);
+        assert_eq!(debugger.run(), Ok(Stop::Halted));
+        assert_eq!(debugger.output(), [4, 6, 3, 5, 6, 3, 5, 2, 1, 0]);
+        assert_eq!(debugger.steps(), 30);
+    }
+
+    #[test]
+    fn execution_errors() {
+        let endless = Computer {
+            registers: [1, 0, 0],
+            instructions: vec![3, 0],
+            eip: 0,
+        };
+        let mut debugger = Debugger::new(&endless).with_step_limit(100);
+        assert_eq!(
+            debugger.run(),
+            Err(ExecutionError::StepLimit { limit: 100 })
+        );
+        assert_eq!(debugger.steps(), 100);
+
+        let reserved = Computer {
+            registers: [1, 0, 0],
+            instructions: vec![5, 4, 0, 7],
+            eip: 0,
+        };
+        let mut debugger = Debugger::new(&reserved);
+        assert_eq!(
+            debugger.run(),
+            Err(ExecutionError::ReservedOperand { eip: 2 })
+        );
+        assert_eq!(debugger.output(), [1]);
+    }
+
+    #[test]
+    fn level2_given_example() {
+        let test_input = include_str!("./test_input/day17_quine.txt");
+        assert_eq!(&level2(test_input), "117440")
+    }
+
+    fn program(instructions: &[u8]) -> Computer {
+        Computer {
+            registers: [0; 3],
+            instructions: instructions.to_vec(),
+            eip: 0,
+        }
+    }
+
+    #[test]
+    fn loop_shapes() {
+        let shape = |instructions: &[u8]| program(instructions).loop_shift();
+        assert_eq!(
+            shape(&[2, 4, 1, 1, 7, 5, 4, 6, 1, 4, 0, 3, 5, 5, 3, 0]),
+            Ok(3)
+        );
+        assert_eq!(shape(&[0, 2, 5, 4, 3, 0]), Ok(2));
+        assert_eq!(shape(&[0, 3, 5, 4]), Err(ShapeError::NoJump));
+        assert_eq!(shape(&[0, 3, 5, 4, 3]), Err(ShapeError::OddLength));
+        assert_eq!(
+            shape(&[0, 3, 3, 0, 5, 4, 3, 0]),
+            Err(ShapeError::JumpNotLast { eip: 2 })
+        );
+        assert_eq!(
+            shape(&[0, 3, 5, 4, 3, 2]),
+            Err(ShapeError::JumpTarget { target: 2 })
+        );
+        assert_eq!(
+            shape(&[0, 3, 5, 4, 5, 4, 3, 0]),
+            Err(ShapeError::Outputs { count: 2 })
+        );
+        assert_eq!(
+            shape(&[0, 3, 0, 3, 5, 4, 3, 0]),
+            Err(ShapeError::Shifts { count: 2 })
+        );
+        assert_eq!(
+            shape(&[0, 5, 5, 4, 3, 0]),
+            Err(ShapeError::ShiftOperand { eip: 0 })
+        );
+        assert_eq!(
+            shape(&[1, 1, 0, 3, 5, 5, 3, 0]),
+            Err(ShapeError::CarriedRegister {
+                register: 'b',
+                eip: 0
+            })
+        );
+        assert_eq!(
+            shape(&[0, 7, 5, 4, 3, 0]),
+            Err(ShapeError::Invalid(ExecutionError::ReservedOperand {
+                eip: 0
+            }))
+        );
+    }
+
+    #[test]
+    fn quine_fallback() {
+        let example = program(&[0, 3, 5, 4, 3, 0]);
+        assert_eq!(example.quine_bounded(120_000), Some(117440));
+        assert_eq!(example.quine_bounded(100_000), None);
+
+        let error = program(&[0, 3, 5, 4]).find_quine(100).unwrap_err();
+        assert_eq!(
+            error,
+            QuineError::NotFound {
+                shape: ShapeError::NoJump,
+                searched: 100
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "the program has no jnz, and no value of a up to 100 reproduces it"
+        );
+        assert_eq!(
+            program(&[0, 1, 5, 4, 3, 0]).find_quine(100),
+            Err(QuineError::NoSolution)
+        );
+    }
+
+    /// A xorshift generator, so the property tests are reproducible.
+    fn random_values(mut state: u64) -> impl Iterator<Item = u64> {
+        std::iter::repeat_with(move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        })
+    }
+
+    #[test]
+    fn assembly_round_trip() {
+        let source = "adv 3\nbxl 5\nbst a\njnz 0\nbxc 6\nout c\nbdv b\ncdv 7\n";
+        let instructions = assemble(source).unwrap();
+        assert_eq!(
+            instructions,
+            [0, 3, 1, 5, 2, 4, 3, 0, 4, 6, 5, 6, 6, 5, 7, 7]
+        );
+        assert_eq!(to_assembly(&instructions).as_deref(), Some(source));
+        assert_eq!(assemble(&disassemble(&instructions)), Ok(instructions));
+        assert_eq!(
+            assemble("; setup\n\n  bxc   ; ignores its operand\n0 out 4"),
+            Err(AssemblyError {
+                line: 4,
+                kind: AssemblyErrorKind::AddressMismatch {
+                    expected: 2,
+                    found: 0
+                }
+            })
+        );
+        assert_eq!(assemble("bxc\n2 out 4"), Ok(vec![4, 0, 5, 4]));
+        assert_eq!(to_assembly(&[0, 3, 5]), None);
+
+        let mut values = random_values(17);
+        for _ in 0..200 {
+            let length = 2 * (values.next().unwrap() % 10) as usize;
+            let program = values
+                .by_ref()
+                .take(length)
+                .map(|value| (value % 8) as u8)
+                .collect_vec();
+            let source = to_assembly(&program).unwrap();
+            assert_eq!(assemble(&source), Ok(program));
+        }
+    }
+
+    #[test]
+    fn assembly_errors() {
+        let error = |source| assemble(source).unwrap_err();
+        assert_eq!(
+            error("adv 3\nmul 2"),
+            AssemblyError {
+                line: 2,
+                kind: AssemblyErrorKind::UnknownMnemonic("mul".to_string())
+            }
+        );
+        assert_eq!(error("\n\nout").line, 3);
+        assert_eq!(error("\n\nout").kind, AssemblyErrorKind::MissingOperand);
+        assert_eq!(
+            error("bxl a").kind,
+            AssemblyErrorKind::InvalidOperand("a".to_string())
+        );
+        assert_eq!(
+            error("jnz 8").kind,
+            AssemblyErrorKind::InvalidOperand("8".to_string())
+        );
+        assert_eq!(
+            error("out a b").kind,
+            AssemblyErrorKind::TrailingText("b".to_string())
+        );
+        assert_eq!(error("out a b").to_string(), "line 1: unexpected \"b\"");
+    }
+
+    #[test]
+    fn interpreter_properties() {
+        let run = |source, a| {
+            let computer = Computer::assembled(source, [a, 0, 0]).unwrap();
+            let mut debugger = Debugger::new(&computer).with_step_limit(1000);
+            let result = debugger.run();
+            (result, debugger.output().to_vec(), debugger.registers())
+        };
+        for a in random_values(5).take(200) {
+            let (_, output, _) = run("adv 2\nout a", a);
+            assert_eq!(output, [((a >> 2) % 8) as u8]);
+
+            let (_, output, registers) = run("bst a\nbxl 5\ncdv b\nbxc\nout b", a);
+            let b = (a % 8) ^ 5;
+            assert_eq!(registers[2], a >> b);
+            assert_eq!(output, [((b ^ (a >> b)) % 8) as u8]);
+
+            // One output per octal digit, least significant first.
+            let (result, output, _) = run("out a\nadv 3\njnz 0", a);
+            assert_eq!(result, Ok(Stop::Halted));
+            let digits = successors(Some(a), |a| Some(a >> 3).filter(|a| *a != 0));
+            assert_eq!(output, digits.map(|a| (a % 8) as u8).collect_vec());
+
+            let (_, _, registers) = run("adv 3\nadv 3\nbdv 3\ncdv 6", a);
+            assert_eq!(registers, [a >> 6, a >> 9, a >> 6]);
+        }
+        let (result, output, _) = run("out 3\nout 7", 1);
+        assert_eq!(result, Err(ExecutionError::ReservedOperand { eip: 2 }));
+        assert_eq!(output, [3]);
+        let (result, ..) = run("jnz 0", 1);
+        assert_eq!(result, Err(ExecutionError::StepLimit { limit: 1000 }));
+    }
 }
@@ -1,6 +1,4 @@
 use std::{
-    cmp::Reverse,
-    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
     io::{self, stdout},
     time::Duration,
 };
@@ -13,12 +11,18 @@ use crossterm::{
     terminal::Clear,
 };
 
-use crate::util::prelude::*;
+use crate::util::{
+    dsu::KeyedDsu,
+    image::{hash_color, Raster, Rgb},
+    prelude::*,
+    search::astar,
+    sparse::{SparseGrid, SparsePos},
+};
 
 #[derive(Debug)]
 struct Map {
     side_length: u16,
-    walls: HashSet<[u16; 2]>,
+    walls: SparseGrid<()>,
 }
 
 impl Map {
@@ -27,55 +31,42 @@ impl Map {
         side_length: u16,
         line_count: usize,
     ) -> anyhow::Result<Self> {
-        let walls: anyhow::Result<HashSet<_>> = input
-            .lines()
-            .take(line_count)
-            .map(|line| {
-                let (start, end) = line.split_once(',').ok_or(anyhow!("no comma"))?;
-                Ok([start.parse()?, end.parse()?])
-            })
-            .collect();
-        Ok(Self {
-            side_length,
-            walls: walls.context("parse")?,
-        })
+        let mut walls = SparseGrid::new();
+        for line in Input::new(input).lines().take(line_count) {
+            let (x, y) = line.split_once(',').ok_or(anyhow!("no comma"))?;
+            let pos: SparsePos = [x.parse().context("parse")?, y.parse().context("parse")?];
+            if pos.iter().any(|c| !(0..=side_length as i32).contains(c)) {
+                return Err(anyhow!("wall outside of map"));
+            }
+            walls.insert(pos, ());
+        }
+        Ok(Self { side_length, walls })
     }
 
     fn shortest_path(&self) -> Option<usize> {
-        let start_pos = [0, 0];
-        let end_pos = [self.side_length, self.side_length];
-
-        let mut visited = HashSet::new();
-        let mut queue = BinaryHeap::new();
-        queue.push((Reverse(2 * self.side_length as usize), 0, start_pos));
-
-        while let Some((_, steps, pos)) = queue.pop() {
-            if !visited.insert(pos) {
-                continue;
-            }
-            if pos == end_pos {
-                return Some(steps);
-            }
-
-            let deltas = [[0, -1], [1, 0], [0, 1], [-1, 0]];
-
-            queue.extend(deltas.into_iter().flat_map(|delta| {
-                let new_x = pos[0].checked_add_signed(delta[0])?;
-                let new_y = pos[1].checked_add_signed(delta[1])?;
-                let new_pos = (new_x <= self.side_length && new_y <= self.side_length)
-                    .then_some([new_x, new_y])?;
-                if visited.contains(&new_pos) || self.walls.contains(&new_pos) {
-                    return None;
-                };
+        let side_length = self.side_length as usize;
+        let start_pos = GridPos { x: 0, y: 0 };
+        let end_pos = GridPos {
+            x: side_length,
+            y: side_length,
+        };
 
-                let new_steps = steps + 1;
-                let new_score = new_steps
-                    + self.side_length.abs_diff(new_x) as usize
-                    + self.side_length.abs_diff(new_y) as usize;
-                Some((Reverse(new_score), new_steps, new_pos))
-            }));
-        }
-        None
+        let size = side_length + 1;
+        let neighbors = |pos: &GridPos| {
+            let pos = *pos;
+            Direction::ALL
+                .into_iter()
+                .filter_map(move |direction| pos.step_within(direction, size, size))
+                .filter(|new_pos| !self.walls.contains(&(*new_pos).into()))
+                .map(|new_pos| (new_pos, 1))
+        };
+        let (steps, _) = astar(
+            start_pos,
+            neighbors,
+            |pos| pos.dist(&end_pos),
+            |pos| *pos == end_pos,
+        )?;
+        Some(steps)
     }
 }
 
@@ -98,33 +89,29 @@ enum WallPos {
 #[derive(Debug)]
 struct ConnectedComponentMap {
     side_length: u16,
-    connected_components: HashMap<WallPos, Option<WallPos>>,
+    /// Walls, with the virtual `TopRight` and `BottomLeft` sentinels standing for everything
+    /// beyond the grid edges.
+    walls: KeyedDsu<WallPos>,
 }
 
 impl ConnectedComponentMap {
     fn new(side_length: u16) -> Self {
-        let mut connected_components = HashMap::new();
-        connected_components.insert(WallPos::TopRight, None);
-        connected_components.insert(WallPos::BottomLeft, None);
-        Self {
-            connected_components,
-            side_length,
-        }
+        let mut walls = KeyedDsu::new();
+        walls.insert(WallPos::TopRight);
+        walls.insert(WallPos::BottomLeft);
+        Self { side_length, walls }
     }
 
-    fn sides_connected(&self) -> bool {
-        self.canonical_representative(WallPos::TopRight)
-            .expect("sides are in components")
-            == self
-                .canonical_representative(WallPos::BottomLeft)
-                .expect("sides are in components")
+    fn sides_connected(&mut self) -> bool {
+        self.walls.same(&WallPos::TopRight, &WallPos::BottomLeft)
     }
 
-    fn neighbor(&self, pos: [u16; 2], delta: [i16; 2]) -> WallPos {
-        let Some(new_x) = pos[0].checked_add_signed(delta[0]) else {
+    fn neighbor(&self, pos: [u16; 2], heading: Heading) -> WallPos {
+        let [dx, dy] = heading.delta();
+        let Some(new_x) = pos[0].checked_add_signed(dx as i16) else {
             return WallPos::BottomLeft;
         };
-        let Some(new_y) = pos[1].checked_add_signed(delta[1]) else {
+        let Some(new_y) = pos[1].checked_add_signed(dy as i16) else {
             return WallPos::TopRight;
         };
         if new_x > self.side_length {
@@ -136,90 +123,30 @@ impl ConnectedComponentMap {
         }
     }
 
-    fn canonical_representative(&self, mut pos: WallPos) -> Option<WallPos> {
-        while let Some(parent) = *self.connected_components.get(&pos)? {
-            pos = parent;
-        }
-        debug_assert!(self.connected_components.get(&pos).unwrap().is_none());
-        Some(pos)
-    }
-
-    fn canonical_representative_with_update(&mut self, mut pos: WallPos) -> Option<WallPos> {
-        let mut ancestors = Vec::new();
-        while let Some(parent) = *self.connected_components.get(&pos)? {
-            ancestors.push(pos);
-            pos = parent;
-        }
-        let representative = pos;
-        for ancestor in ancestors {
-            *self
-                .connected_components
-                .get_mut(&ancestor)
-                .expect("already visited") = Some(representative);
-        }
-        Some(representative)
+    fn canonical_representative(&self, pos: WallPos) -> Option<WallPos> {
+        self.walls.root(&pos).copied()
     }
 
     fn add_interior_wall(&mut self, pos: [u16; 2]) {
-        let mut to_update = vec![WallPos::Interior(pos)];
-        to_update.extend(
-            [-1, 0, 1]
-                .into_iter()
-                .cartesian_product([-1, 0, 1])
-                .flat_map(|(x, y)| (x != 0 || y != 0).then_some([x, y]))
-                .flat_map(|delta| {
-                    let mut ancestor_of_neighbor = self.neighbor(pos, delta);
-                    if !self
-                        .connected_components
-                        .contains_key(&ancestor_of_neighbor)
-                    {
-                        return Vec::new();
-                    };
-                    let mut ancestors = vec![ancestor_of_neighbor];
-                    while let Some(parent) = *self
-                        .connected_components
-                        .get(&ancestor_of_neighbor)
-                        .expect("parents are in map")
-                    {
-                        ancestors.push(parent);
-                        ancestor_of_neighbor = parent;
-                    }
-                    ancestors
-                }),
-        );
-        let representative = to_update
-            .last()
-            .copied()
-            .expect("vector started with one value");
-        for ancestor in to_update {
-            self.connected_components.insert(
-                ancestor,
-                (ancestor != representative).then_some(representative),
-            );
+        let wall = WallPos::Interior(pos);
+        self.walls.insert(wall);
+        for heading in Heading::ALL {
+            let neighbor = self.neighbor(pos, heading);
+            if self.walls.contains(&neighbor) {
+                self.walls.union(wall, neighbor);
+            }
         }
     }
 
     fn plot(&self) -> io::Result<()> {
         let mut stdout = stdout();
-        let connected_compononents = self
-            .connected_components
-            .keys()
-            .copied()
-            .into_group_map_by(|pos| self.canonical_representative(*pos).expect("key in map"));
-        for (representative, component) in connected_compononents {
-            let hash = match representative {
-                WallPos::TopRight => 1,
-                WallPos::BottomLeft => 2,
-                WallPos::Interior([x, y]) => 97 * (x as u32) + (y as u32) + 3,
-            };
-            let color = Color::Rgb {
-                r: ((37 * hash + 32) & 255) as u8,
-                g: ((149 * hash + 82) & 255) as u8,
-                b: ((67 * hash + 191) & 255) as u8,
-            };
-            let content = '▉'.stylize().with(color);
+        for component in self.walls.components() {
+            let representative = self
+                .canonical_representative(*component[0])
+                .expect("key in map");
+            let content = '▉'.stylize().with(component_color(representative).into());
             for pos in component {
-                match pos {
+                match *pos {
                     WallPos::TopRight => {
                         for i in 0..=self.side_length {
                             execute!(
@@ -253,13 +180,40 @@ impl ConnectedComponentMap {
     }
 }
 
+fn component_color(representative: WallPos) -> Rgb {
+    hash_color(match representative {
+        WallPos::TopRight => 1,
+        WallPos::BottomLeft => 2,
+        WallPos::Interior([x, y]) => 97 * (x as u32) + (y as u32) + 3,
+    })
+}
+
+/// The corrupted bytes up to the first one blocking the exit, coloured by connected component.
+pub fn corrupted_bytes_image(input: &str, side_length: u16) -> Raster {
+    let mut connected_component_map = ConnectedComponentMap::new(side_length);
+    for line in Input::new(input).lines() {
+        let (x, y) = line.split_once(',').expect("line has comma");
+        connected_component_map
+            .add_interior_wall([x.parse().expect("parse"), y.parse().expect("parse")]);
+        if connected_component_map.sides_connected() {
+            break;
+        }
+    }
+    let size = side_length as usize + 1;
+    Raster::from_cells(size, size, 8, |pos| {
+        connected_component_map
+            .canonical_representative(WallPos::Interior([pos.x as u16, pos.y as u16]))
+            .map_or(Rgb::WHITE, component_color)
+    })
+}
+
 fn level2_parametric(input: &str, side_length: u16) -> Option<(u16, u16)> {
     let plot = std::env::var_os("PLOT").is_some();
     if plot {
         execute!(stdout(), Clear(crossterm::terminal::ClearType::All), Hide).unwrap();
     }
     let mut connected_component_map = ConnectedComponentMap::new(side_length);
-    for line in input.trim().lines() {
+    for line in Input::new(input).lines() {
         let (x, y) = line.split_once(',').expect("line has comma");
         let x = x.parse().expect("parse");
         let y = y.parse().expect("parse");
@@ -295,6 +249,15 @@ pub fn level2(input: &str) -> String {
 mod test {
     use super::*;
 
+    #[test]
+    fn corrupted_bytes_raster() {
+        let test_input = include_str!("./test_input/day18.txt");
+        let raster = corrupted_bytes_image(test_input, 6);
+        assert_eq!((raster.width(), raster.height()), (56, 56));
+        assert_eq!(raster.get(0, 0), Some(Rgb::WHITE));
+        assert_ne!(raster.get(6 * 8, 8), Some(Rgb::WHITE));
+    }
+
     #[test]
     fn level1_given_example() {
         let test_input = include_str!("./test_input/day18.txt");
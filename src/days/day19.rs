@@ -3,13 +3,13 @@ use std::collections::{HashMap, VecDeque};
 use crate::util::prelude::*;
 
 pub fn level1(input: &str) -> usize {
-    let mut lines = input.trim().lines();
+    let input = Input::new(input);
+    let (design_block, towels) = input.blocks().collect_tuple().expect("two blocks");
 
-    let design_line = lines.next().expect("designs");
-    let pattern = design_line.replace(", ", "|");
+    let pattern = design_block.as_str().trim_end().replace(", ", "|");
     let r = regex::Regex::new(&format!("^({})+$", pattern)).expect("compile regex");
 
-    lines.filter(|line| r.is_match(line)).count()
+    towels.lines().filter(|line| r.is_match(line)).count()
 }
 
 fn match_count<'a>(
@@ -30,15 +30,16 @@ fn match_count<'a>(
 }
 
 pub fn level2(input: &str) -> usize {
-    let mut lines = input.trim().lines();
+    let input = Input::new(input);
+    let (design_block, towels) = input.blocks().collect_tuple().expect("two blocks");
 
-    let designs = lines.next().expect("designs").split(", ").collect_vec();
+    let designs = design_block.as_str().trim_end().split(", ").collect_vec();
 
     let mut cache = HashMap::new();
     cache.insert("", 1);
 
-    lines
-        .filter(|line| !line.is_empty())
+    towels
+        .lines()
         .map(|line| match_count(line, &designs, &mut cache))
         .sum()
 }
@@ -1,9 +1,9 @@
-use std::{iter::once, usize};
+use std::iter::once;
 
 use crate::util::prelude::*;
 
 pub fn level1(input: &str) -> usize {
-    input
+    Input::new(input)
         .lines()
         .filter(|line| {
             let numbers: Vec<u32> = line
@@ -21,7 +21,7 @@ pub fn level1(input: &str) -> usize {
 }
 
 pub fn level2(input: &str) -> usize {
-    input
+    Input::new(input)
         .lines()
         .filter(|line| {
             let numbers: Vec<u32> = line
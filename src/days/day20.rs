@@ -13,7 +13,10 @@ use crossterm::{
 use nom::combinator::opt;
 use rayon::prelude::*;
 
-use crate::util::prelude::*;
+use crate::util::{
+    image::{Rgb, Svg},
+    prelude::*,
+};
 
 #[derive(Debug)]
 struct Map {
@@ -29,51 +32,32 @@ struct Map {
 
 impl Map {
     fn parse_input(input: &str) -> Result<Self, &'static str> {
-        let mut width = 0;
-        let mut height = 0;
-        let mut field_set = HashSet::new();
-        let mut start_pos = None;
-        let mut end_pos = None;
-        for (y, line) in input.lines().enumerate() {
-            let y = y as u16;
-            height = y + 1;
-            for (x, c) in line.chars().enumerate() {
-                let x = x as u16;
-                if y == 0 {
-                    width = x + 1;
-                }
-                match c {
-                    '#' => {}
-                    '.' => {
-                        field_set.insert([x, y]);
-                    }
-                    'S' => {
-                        start_pos = Some([x, y]);
-                        field_set.insert([x, y]);
-                    }
-                    'E' => {
-                        end_pos = Some([x, y]);
-                        field_set.insert([x, y]);
-                    }
-                    _ => return Err("unexpected char"),
-                }
-            }
-        }
-
-        let start_pos = start_pos.ok_or("no start")?;
-        let end_pos = end_pos.ok_or("no end")?;
+        let grid = Input::new(input)
+            .grid(|c| matches!(c, '#' | '.' | 'S' | 'E').then_some(c))
+            .map_err(|_| "unexpected char")?;
+        let width = grid.length as u16;
+        let height = grid.height as u16;
+        let to_pos = |pos: GridPos| [pos.x as u16, pos.y as u16];
+        let field_set: HashSet<_> = grid
+            .enumerate()
+            .filter(|(_, c)| **c != '#')
+            .map(|(pos, _)| to_pos(pos))
+            .collect();
+        let start_pos = grid.find(&'S').map(to_pos).ok_or("no start")?;
+        let end_pos = grid.find(&'E').map(to_pos).ok_or("no end")?;
 
         let mut visited = HashSet::new();
         let mut fields = vec![start_pos];
 
         let mut pos = start_pos;
         loop {
-            let next_pos = [[0, -1], [1, 0], [0, 1], [-1, 0]]
+            let next_pos = Direction::ALL
                 .into_iter()
-                .flat_map(|delta| {
+                .flat_map(|direction| {
+                    let [dx, dy] = direction.delta();
                     let new_pos = [
-                        pos[0].checked_add_signed(delta[0])?,
-                        pos[1].checked_add_signed(delta[1])?,
+                        pos[0].checked_add_signed(dx as i16)?,
+                        pos[1].checked_add_signed(dy as i16)?,
                     ];
                     (field_set.contains(&new_pos) && !visited.contains(&new_pos)).then_some(new_pos)
                 })
@@ -200,6 +184,27 @@ impl Map {
     }
 }
 
+/// The racetrack with the walls in grey and the path drawn from start to end.
+pub fn racetrack_svg(input: &str) -> Svg {
+    let map = Map::parse_input(input).expect("parse");
+    let path = map
+        .fields
+        .iter()
+        .map(|pos| GridPos::from((pos[0] as usize, pos[1] as usize)))
+        .collect_vec();
+    let mut svg = Svg::new(map.width as usize, map.height as usize, 8);
+    svg.cells(|_| Some(Rgb::GREY));
+    for pos in &path {
+        svg.cell(pos, Rgb::WHITE);
+    }
+    svg.path(&path, Rgb::from(Color::Blue), 3.0);
+    if let (Some(start), Some(end)) = (path.first(), path.last()) {
+        svg.cell(start, Rgb::from(Color::Green));
+        svg.cell(end, Rgb::RED);
+    }
+    svg
+}
+
 pub fn level1(input: &str) -> usize {
     let map = Map::parse_input(input).expect("parse");
     map.cheat_savings(2, 100).count()
@@ -214,6 +219,17 @@ pub fn level2(input: &str) -> usize {
 mod test {
     use super::*;
 
+    #[test]
+    fn racetrack_image() {
+        let test_input = include_str!("./test_input/day20.txt");
+        let svg = racetrack_svg(test_input).to_svg_string();
+        assert!(svg.contains(r#"width="120" height="120" viewBox="0 0 120 120""#));
+        let end = Rgb::RED.hex();
+        assert!(svg.contains(&format!(
+            r#"<rect x="40" y="56" width="8" height="8" fill="{end}"/>"#
+        )));
+    }
+
     #[test]
     fn level1_given_example() {
         let test_input = include_str!("./test_input/day20.txt");
@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+
+use crate::util::{input::ints, prelude::*};
+
+/// A keypad layout with positions as `[x, y]`. The one cell without a button has to be avoided,
+/// since a robot arm pointing at it panics.
+struct Keypad {
+    keys: HashMap<char, [i8; 2]>,
+    gap: [i8; 2],
+}
+
+impl Keypad {
+    /// Builds a keypad from its rows, with a space for the gap.
+    fn new(rows: &[&str]) -> Self {
+        let mut keys = HashMap::new();
+        let mut gap = None;
+        for (y, row) in rows.iter().enumerate() {
+            for (x, key) in row.chars().enumerate() {
+                let pos = [x as i8, y as i8];
+                if key == ' ' {
+                    gap = Some(pos);
+                } else {
+                    keys.insert(key, pos);
+                }
+            }
+        }
+        Self {
+            keys,
+            gap: gap.expect("a keypad has a gap"),
+        }
+    }
+
+    fn numeric() -> Self {
+        Self::new(&["789", "456", "123", " 0A"])
+    }
+
+    fn directional() -> Self {
+        Self::new(&[" ^A", "<v>"])
+    }
+
+    /// The candidate button sequences for moving from one key to another and pressing it, each
+    /// ending in `A`. Only the two L-shaped paths are worth trying: every change of direction
+    /// costs an extra trip on the keypad above, so a zig-zag is never cheaper.
+    fn paths(&self, from: char, to: char) -> Vec<String> {
+        let [fx, fy] = self.keys[&from];
+        let [tx, ty] = self.keys[&to];
+        let horizontal = if tx < fx { "<" } else { ">" }.repeat(fx.abs_diff(tx) as usize);
+        let vertical = if ty < fy { "^" } else { "v" }.repeat(fy.abs_diff(ty) as usize);
+        let mut paths = Vec::with_capacity(2);
+        if [tx, fy] != self.gap {
+            paths.push(format!("{horizontal}{vertical}A"));
+        }
+        if [fx, ty] != self.gap {
+            paths.push(format!("{vertical}{horizontal}A"));
+        }
+        paths.dedup();
+        paths
+    }
+}
+
+/// A numeric keypad operated through a chain of robots on directional keypads, with the last
+/// directional keypad in the hands of a human.
+pub struct KeypadChain {
+    numeric: Keypad,
+    directional: Keypad,
+    robots: usize,
+    /// The human presses for moving between two directional keys `depth` robots away.
+    cache: HashMap<(char, char, usize), usize>,
+}
+
+impl KeypadChain {
+    /// A chain with `robots` directional keypads between the human and the numeric keypad.
+    pub fn new(robots: usize) -> Self {
+        Self {
+            numeric: Keypad::numeric(),
+            directional: Keypad::directional(),
+            robots,
+            cache: HashMap::new(),
+        }
+    }
+
+    /// The human presses needed to type `sequence` on a directional keypad `depth` robots
+    /// away. Every arm starts on `A`, and is back on it after typing a sequence.
+    fn sequence_presses(&mut self, sequence: &str, depth: usize) -> usize {
+        std::iter::once('A')
+            .chain(sequence.chars())
+            .tuple_windows()
+            .map(|(from, to)| self.step_presses(from, to, depth))
+            .sum()
+    }
+
+    fn step_presses(&mut self, from: char, to: char, depth: usize) -> usize {
+        if depth == 0 {
+            return 1;
+        }
+        if let Some(presses) = self.cache.get(&(from, to, depth)) {
+            return *presses;
+        }
+        let presses = self
+            .directional
+            .paths(from, to)
+            .iter()
+            .map(|path| self.sequence_presses(path, depth - 1))
+            .min()
+            .expect("every key can be reached");
+        self.cache.insert((from, to, depth), presses);
+        presses
+    }
+
+    /// The fewest human presses that type `code` on the numeric keypad.
+    pub fn presses(&mut self, code: &str) -> usize {
+        std::iter::once('A')
+            .chain(code.chars())
+            .tuple_windows()
+            .map(|(from, to)| {
+                self.numeric
+                    .paths(from, to)
+                    .iter()
+                    .map(|path| self.sequence_presses(path, self.robots))
+                    .min()
+                    .expect("every key can be reached")
+            })
+            .sum()
+    }
+}
+
+/// The sum over all codes of the presses times the numeric part of the code.
+pub fn complexity(input: &str, robots: usize) -> usize {
+    let mut chain = KeypadChain::new(robots);
+    Input::new(input)
+        .lines()
+        .map(|code| {
+            let value = ints(code).next().expect("numeric code") as usize;
+            chain.presses(code) * value
+        })
+        .sum()
+}
+
+pub fn level1(input: &str) -> usize {
+    complexity(input, 2)
+}
+
+pub fn level2(input: &str) -> usize {
+    complexity(input, 25)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn level1_given_example() {
+        let test_input = include_str!("./test_input/day21.txt");
+        assert_eq!(level1(test_input), 126384)
+    }
+
+    #[test]
+    fn level2_given_example() {
+        let test_input = include_str!("./test_input/day21.txt");
+        assert_eq!(level2(test_input), 154115708116294)
+    }
+
+    #[test]
+    fn keypad_chain() {
+        let numeric = Keypad::numeric();
+        assert_eq!(numeric.paths('A', '1'), ["^<<A"]);
+        assert_eq!(numeric.paths('7', '0'), [">vvvA"]);
+        assert_eq!(numeric.paths('2', '9'), [">^^A", "^^>A"]);
+        assert_eq!(Keypad::directional().paths('<', 'A'), [">>^A"]);
+
+        let mut chain = KeypadChain::new(2);
+        let presses = ["029A", "980A", "179A", "456A", "379A"].map(|code| chain.presses(code));
+        assert_eq!(presses, [68, 60, 68, 64, 64]);
+        assert_eq!(KeypadChain::new(0).presses("029A"), "<A^A>^^AvvvA".len());
+    }
+}
@@ -7,7 +7,7 @@ use std::{
 use crate::util::prelude::*;
 
 pub fn level1(input: &str) -> i64 {
-    input
+    Input::new(input)
         .lines()
         .map(|line| line.parse().unwrap())
         .map(|secret| {
@@ -44,7 +44,7 @@ fn first_buy(secret: i64) -> HashMap<[i8; 4], i64> {
 }
 
 pub fn level2(input: &str) -> i64 {
-    input
+    Input::new(input)
         .lines()
         .map(|line| line.parse().unwrap())
         .map(first_buy)
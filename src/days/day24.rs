@@ -1,5 +1,7 @@
-use core::panic;
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    fmt::Display,
+};
 
 use nom::{
     branch::alt,
@@ -9,13 +11,12 @@ use nom::{
     IResult, Parser,
 };
 use nom_supreme::{
-    error::ErrorTree, final_parser::final_parser, multi::collect_separated_terminated,
-    tag::complete::tag, ParserExt,
+    error::ErrorTree, multi::collect_separated_terminated, tag::complete::tag, ParserExt,
 };
 
-use crate::util::prelude::*;
+use crate::util::{dot::Dot, prelude::*};
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Operation {
     And,
     Or,
@@ -23,6 +24,17 @@ enum Operation {
 }
 
 impl Operation {
+    const ALL: [Self; 3] = [Self::And, Self::Or, Self::Xor];
+
+    /// The input rows of the gate's single-output cover in BLIF, in canonical order.
+    fn blif_cover(&self) -> &'static [&'static str] {
+        match self {
+            Operation::And => &["11"],
+            Operation::Or => &["1-", "-1"],
+            Operation::Xor => &["01", "10"],
+        }
+    }
+
     fn apply(&self, inputs: [u8; 2]) -> u8 {
         match self {
             Operation::And => inputs[0] & inputs[1],
@@ -32,15 +44,150 @@ impl Operation {
     }
 }
 
-#[derive(Debug)]
+impl Display for Operation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Operation::And => write!(f, "AND"),
+            Operation::Or => write!(f, "OR"),
+            Operation::Xor => write!(f, "XOR"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum EvalError<'a> {
+    /// A gate reads a wire that is neither an input nor the output of a gate.
+    Undriven(&'a str),
+    /// The wires on a loop, each feeding into the next and the last into the first.
+    Cycle(Vec<&'a str>),
+    /// An input wire that is not a numbered `x` or `y` operand bit.
+    UnknownInput(&'a str),
+}
+
+impl Display for EvalError<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EvalError::Undriven(wire) => write!(f, "wire {wire} has no driver"),
+            EvalError::Cycle(wires) => write!(f, "cycle through {}", wires.join(" <- ")),
+            EvalError::UnknownInput(wire) => write!(f, "input {wire} is not an operand bit"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum VerifyError<'a> {
+    Eval(EvalError<'a>),
+    WrongSum {
+        bit: usize,
+        x: u64,
+        y: u64,
+        expected: u128,
+        found: u128,
+    },
+}
+
+impl VerifyError<'_> {
+    /// The lowest wrong output bit, or zero if the circuit can't be evaluated at all.
+    fn bit(&self) -> usize {
+        match self {
+            VerifyError::Eval(_) => 0,
+            VerifyError::WrongSum { bit, .. } => *bit,
+        }
+    }
+}
+
+impl Display for VerifyError<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VerifyError::Eval(error) => error.fmt(f),
+            VerifyError::WrongSum {
+                bit,
+                x,
+                y,
+                expected,
+                found,
+            } => write!(
+                f,
+                "bit {bit} is wrong: {x} + {y} gave {found} instead of {expected}"
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum BlifErrorKind {
+    UnknownDirective(String),
+    GateArity(usize),
+    UnsupportedCover(Vec<String>),
+    CoverOutsideGate,
+    InvalidValue(String),
+}
+
+/// An error in a BLIF file on a one-based line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct BlifError {
+    line: usize,
+    kind: BlifErrorKind,
+}
+
+impl Display for BlifError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            BlifErrorKind::UnknownDirective(directive) => {
+                write!(f, "unsupported directive {directive:?}")
+            }
+            BlifErrorKind::GateArity(count) => {
+                write!(f, "gate has {count} inputs instead of two")
+            }
+            BlifErrorKind::UnsupportedCover(rows) => {
+                write!(f, "cover {rows:?} is not AND, OR or XOR")
+            }
+            BlifErrorKind::CoverOutsideGate => write!(f, "cover row outside of .names"),
+            BlifErrorKind::InvalidValue(value) => write!(f, "invalid input value {value:?}"),
+        }
+    }
+}
+
+impl std::error::Error for BlifError {}
+
+/// A `.names` block whose cover rows are still being read.
+struct BlifGate<'a> {
+    line: usize,
+    inputs: [&'a str; 2],
+    output: &'a str,
+    rows: Vec<&'a str>,
+}
+
+impl<'a> BlifGate<'a> {
+    fn finish(self) -> Result<(&'a str, (Operation, [&'a str; 2])), BlifError> {
+        let op = Operation::ALL
+            .into_iter()
+            .find(|op| {
+                self.rows
+                    .iter()
+                    .sorted()
+                    .eq(op.blif_cover().iter().sorted())
+            })
+            .ok_or_else(|| BlifError {
+                line: self.line,
+                kind: BlifErrorKind::UnsupportedCover(
+                    self.rows.iter().map(|row| row.to_string()).collect(),
+                ),
+            })?;
+        Ok((self.output, (op, self.inputs)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 struct Computer<'a> {
     inputs: HashMap<&'a str, u8>,
     nodes: HashMap<&'a str, (Operation, [&'a str; 2])>,
 }
 
 impl<'a> Computer<'a> {
-    pub fn from_input(input: &'a str) -> Result<Self, ErrorTree<&'a str>> {
-        final_parser(Self::parse_computer)(input)
+    pub fn from_input(input: &'a Input) -> ParseFinalResult<'a, Self> {
+        input.parse(Self::parse_computer)
     }
 
     fn parse_computer(input: &'a str) -> IResult<&'a str, Self, ErrorTree<&'a str>> {
@@ -73,157 +220,507 @@ impl<'a> Computer<'a> {
         .parse(input)
     }
 
-    fn results(self) -> HashMap<&'a str, u8> {
-        let mut result = self.inputs;
-
+    /// The value of every wire. Gates are evaluated on demand with an explicit stack, so a
+    /// wire that depends on itself is reported instead of looping.
+    fn evaluate(&self) -> Result<HashMap<&'a str, u8>, EvalError<'a>> {
+        let mut values = self.inputs.clone();
+        let mut on_stack = HashSet::new();
         for node in self.nodes.keys() {
-            if result.contains_key(node) {
+            if values.contains_key(node) {
                 continue;
             }
-            let mut stack = vec![node];
+            let mut stack = vec![*node];
+            on_stack.insert(*node);
 
-            while let Some(visited_node) = stack.last().copied() {
-                let (op, [left, right]) =
-                    self.nodes.get(visited_node).expect("only visit known keys");
-                let Some(left_result) = result.get(left) else {
-                    stack.push(left);
-                    continue;
+            while let Some(wire) = stack.last().copied() {
+                let Some((op, [left, right])) = self.nodes.get(wire) else {
+                    return Err(EvalError::Undriven(wire));
                 };
-                let Some(right_result) = result.get(right) else {
-                    stack.push(right);
-                    continue;
-                };
-                result.insert(visited_node, op.apply([*left_result, *right_result]));
-                stack.pop();
+                match [left, right]
+                    .into_iter()
+                    .find(|input| !values.contains_key(*input))
+                {
+                    Some(input) if on_stack.contains(input) => {
+                        let start = stack.iter().position(|wire| wire == input).unwrap();
+                        return Err(EvalError::Cycle(stack.split_off(start)));
+                    }
+                    Some(input) => {
+                        stack.push(input);
+                        on_stack.insert(input);
+                    }
+                    None => {
+                        values.insert(wire, op.apply([values[left], values[right]]));
+                        stack.pop();
+                        on_stack.remove(wire);
+                    }
+                }
             }
         }
-        result
+        Ok(values)
     }
 
-    fn swap(&mut self, src: &'a str, tgt: &'a str) {
-        let Some(src_op) = self.nodes.remove(&src) else {
-            return;
+    /// Sets the `x` and `y` input wires to the bits of the operands.
+    fn set_operands(&mut self, x: u64, y: u64) -> Result<(), EvalError<'a>> {
+        for (wire, value) in self.inputs.iter_mut() {
+            let (operand, bit) = match wire.split_at_checked(1) {
+                Some(("x", bit)) => (x, bit),
+                Some(("y", bit)) => (y, bit),
+                _ => return Err(EvalError::UnknownInput(wire)),
+            };
+            let bit: u32 = bit.parse().map_err(|_| EvalError::UnknownInput(wire))?;
+            *value = operand
+                .checked_shr(bit)
+                .map_or(0, |operand| (operand & 1) as u8);
+        }
+        Ok(())
+    }
+
+    /// The number formed by the wires starting with `prefix`, with the wire numbers as bit
+    /// positions.
+    fn read(values: &HashMap<&str, u8>, prefix: char) -> u128 {
+        values
+            .iter()
+            .filter(|(wire, _)| wire.starts_with(prefix))
+            .map(|(wire, value)| {
+                let bit: u32 = wire[1..].parse().expect("output wires are numbered");
+                (*value as u128) << bit
+            })
+            .fold(0, |acc, bit| acc | bit)
+    }
+
+    /// What the circuit outputs on `z` for the given operands. The sum is wider than the
+    /// operands, to keep the final carry of a 64-bit adder.
+    fn add(&mut self, x: u64, y: u64) -> Result<u128, EvalError<'a>> {
+        self.set_operands(x, y)?;
+        Ok(Self::read(&self.evaluate()?, 'z'))
+    }
+
+    /// Checks `z = x + y` on edge cases (zeros, all ones, single bits, carry chains) and
+    /// `random_rounds` random operand pairs. On failure, returns the case with the lowest
+    /// wrong output bit.
+    fn verify(&mut self, random_rounds: usize) -> Result<(), VerifyError<'a>> {
+        let bits = self.bit_count() as u32;
+        let mask = u64::MAX
+            .checked_shr(64u32.saturating_sub(bits))
+            .unwrap_or(0);
+        let alternating = 0x5555_5555_5555_5555 & mask;
+        let mut cases = vec![
+            (0, 0),
+            (mask, 0),
+            (0, mask),
+            (mask, 1),
+            (mask, mask),
+            (alternating, !alternating & mask),
+            (alternating, alternating),
+        ];
+        for bit in 0..bits {
+            cases.extend([(1 << bit, 0), (0, 1 << bit), (1 << bit, 1 << bit)]);
+        }
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        cases.extend(
+            std::iter::repeat_with(|| {
+                // xorshift64
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                (state & mask, (state >> 32 ^ state) & mask)
+            })
+            .take(random_rounds),
+        );
+
+        let mut lowest: Option<VerifyError> = None;
+        for (x, y) in cases {
+            let found = self.add(x, y).map_err(VerifyError::Eval)?;
+            let expected = x as u128 + y as u128;
+            if found != expected {
+                let bit = (found ^ expected).trailing_zeros() as usize;
+                if lowest.as_ref().is_none_or(|lowest| bit < lowest.bit()) {
+                    lowest = Some(VerifyError::WrongSum {
+                        bit,
+                        x,
+                        y,
+                        expected,
+                        found,
+                    });
+                }
+            }
+        }
+        lowest.map_or(Ok(()), Err)
+    }
+
+    /// Wires whose gate does not fit into a ripple-carry adder: outputs other than the final
+    /// carry must come from an `XOR`, an `XOR` of carries must drive an output, the half sum
+    /// `x XOR y` must feed another `XOR` and every `AND` apart from bit 0 must feed an `OR`.
+    fn validate(&self) -> BTreeSet<&'a str> {
+        let last_output = format!("z{:02}", self.inputs.len() / 2);
+        let feeds = |wire: &str, operation: Operation| {
+            self.nodes
+                .values()
+                .any(|(op, inputs)| *op == operation && inputs.contains(&wire))
         };
-        let Some(tgt_op) = self.nodes.remove(&tgt) else {
-            return;
+        self.nodes
+            .iter()
+            .filter(|(output, (op, inputs))| {
+                let from_inputs = inputs
+                    .iter()
+                    .all(|input| input.starts_with('x') || input.starts_with('y'));
+                let first_bit = inputs.iter().all(|input| input.ends_with("00"));
+                if output.starts_with('z') {
+                    let expected = if **output == last_output {
+                        Operation::Or
+                    } else {
+                        Operation::Xor
+                    };
+                    *op != expected
+                } else {
+                    match op {
+                        Operation::Xor if !from_inputs => true,
+                        Operation::Xor => !feeds(output, Operation::Xor),
+                        Operation::And if first_bit => false,
+                        Operation::And => !feeds(output, Operation::Or),
+                        Operation::Or => false,
+                    }
+                }
+            })
+            .map(|(output, _)| *output)
+            .collect()
+    }
+
+    /// The netlist as a DOT graph, with inputs and outputs on their own ranks, gates labelled by
+    /// their operation and `flagged` wires in red.
+    fn to_dot(&self, flagged: &BTreeSet<&str>) -> Dot {
+        let mut dot = Dot::digraph("circuit");
+        dot.attribute("rankdir", "LR");
+        let color = |wire: &str| {
+            if flagged.contains(wire) {
+                "red"
+            } else {
+                "black"
+            }
         };
+        for input in self.inputs.keys().sorted() {
+            dot.node(input, &[("shape", "circle"), ("color", color(input))]);
+        }
+        for (output, (op, inputs)) in self.nodes.iter().sorted_by_key(|(output, _)| **output) {
+            let label = format!("{output}\\n{op}");
+            let shape = match op {
+                Operation::And => "box",
+                Operation::Or => "ellipse",
+                Operation::Xor => "diamond",
+            };
+            dot.node(
+                output,
+                &[
+                    ("label", &label),
+                    ("shape", shape),
+                    ("color", color(output)),
+                ],
+            );
+            for input in inputs {
+                dot.edge(input, output, &[]);
+            }
+        }
+        dot.rank("source", self.inputs.keys().sorted());
+        dot.rank(
+            "sink",
+            self.nodes
+                .keys()
+                .filter(|wire| wire.starts_with('z'))
+                .sorted(),
+        );
+        dot
+    }
+
+    fn sorted_wires(&self) -> (Vec<&'a str>, Vec<&'a str>, Vec<&'a str>) {
+        let inputs = self.inputs.keys().copied().sorted().collect_vec();
+        let (outputs, internal) = self
+            .nodes
+            .keys()
+            .copied()
+            .sorted()
+            .partition(|wire| wire.starts_with('z'));
+        (inputs, outputs, internal)
+    }
 
+    /// The netlist as a structural Verilog module built from gate primitives. The input values
+    /// are not part of the netlist and only appear as a comment.
+    fn to_verilog(&self) -> String {
+        let (inputs, outputs, internal) = self.sorted_wires();
+        let mut verilog = String::new();
+        let values = inputs
+            .iter()
+            .map(|wire| format!("{wire}={}", self.inputs[wire]))
+            .join(" ");
+        verilog.push_str(&format!("// input values: {values}\n"));
+        let ports = inputs.iter().chain(&outputs).join(", ");
+        verilog.push_str(&format!("module circuit({ports});\n"));
+        verilog.push_str(&format!("  input {};\n", inputs.join(", ")));
+        verilog.push_str(&format!("  output {};\n", outputs.join(", ")));
+        if !internal.is_empty() {
+            verilog.push_str(&format!("  wire {};\n", internal.join(", ")));
+        }
+        for output in outputs.iter().chain(&internal).sorted() {
+            let (op, [left, right]) = self.nodes[output];
+            let primitive = op.to_string().to_lowercase();
+            verilog.push_str(&format!(
+                "  {primitive} g_{output}({output}, {left}, {right});\n"
+            ));
+        }
+        verilog.push_str("endmodule\n");
+        verilog
+    }
+
+    /// The netlist in the Berkeley Logic Interchange Format. BLIF has no initial values, so the
+    /// input values are kept in `# wire: value` comments, which [`Computer::from_blif`] reads.
+    fn to_blif(&self) -> String {
+        let (inputs, outputs, internal) = self.sorted_wires();
+        let mut blif = String::from(".model circuit\n");
+        blif.push_str(&format!(".inputs {}\n", inputs.join(" ")));
+        blif.push_str(&format!(".outputs {}\n", outputs.join(" ")));
+        for input in &inputs {
+            blif.push_str(&format!("# {input}: {}\n", self.inputs[input]));
+        }
+        for output in outputs.iter().chain(&internal).sorted() {
+            let (op, [left, right]) = self.nodes[output];
+            blif.push_str(&format!(".names {left} {right} {output}\n"));
+            for row in op.blif_cover() {
+                blif.push_str(&format!("{row} 1\n"));
+            }
+        }
+        blif.push_str(".end\n");
+        blif
+    }
+
+    /// Reads the subset of BLIF written by [`Computer::to_blif`]: one model of two-input
+    /// `.names` gates whose covers are AND, OR or XOR. Inputs without a value comment are 0.
+    fn from_blif(blif: &'a str) -> Result<Self, BlifError> {
+        let mut inputs = HashMap::new();
+        let mut values = HashMap::new();
+        let mut nodes = HashMap::new();
+        let mut gate: Option<BlifGate> = None;
+
+        for (index, line) in blif.lines().enumerate() {
+            let error = |kind| BlifError {
+                line: index + 1,
+                kind,
+            };
+            let mut tokens = line.split_whitespace();
+            let Some(first) = tokens.next() else {
+                continue;
+            };
+            if first == "#" {
+                if let (Some(wire), Some(value)) = (tokens.next(), tokens.next()) {
+                    if let Some(wire) = wire.strip_suffix(':') {
+                        let value = value
+                            .parse::<u8>()
+                            .ok()
+                            .filter(|value| *value < 2)
+                            .ok_or_else(|| error(BlifErrorKind::InvalidValue(value.to_string())))?;
+                        values.insert(wire, value);
+                    }
+                }
+                continue;
+            }
+            if first.starts_with('#') {
+                continue;
+            }
+            if first.starts_with('.') {
+                if let Some(gate) = gate.take() {
+                    let (output, node) = gate.finish()?;
+                    nodes.insert(output, node);
+                }
+            }
+            match first {
+                ".model" | ".outputs" | ".end" => {}
+                ".inputs" => inputs.extend(tokens.map(|wire| (wire, 0))),
+                ".names" => {
+                    let wires = tokens.collect_vec();
+                    let [left, right, output] = wires[..] else {
+                        return Err(error(BlifErrorKind::GateArity(
+                            wires.len().saturating_sub(1),
+                        )));
+                    };
+                    gate = Some(BlifGate {
+                        line: index + 1,
+                        inputs: [left, right],
+                        output,
+                        rows: Vec::new(),
+                    });
+                }
+                directive if directive.starts_with('.') => {
+                    return Err(error(BlifErrorKind::UnknownDirective(
+                        directive.to_string(),
+                    )));
+                }
+                row => {
+                    let Some(BlifGate { rows, .. }) = &mut gate else {
+                        return Err(error(BlifErrorKind::CoverOutsideGate));
+                    };
+                    if tokens.next() != Some("1") {
+                        return Err(error(BlifErrorKind::UnsupportedCover(vec![
+                            line.to_string()
+                        ])));
+                    }
+                    rows.push(row);
+                }
+            }
+        }
+        if let Some(gate) = gate {
+            let (output, node) = gate.finish()?;
+            nodes.insert(output, node);
+        }
+        for (wire, value) in values {
+            if let Some(input) = inputs.get_mut(wire) {
+                *input = value;
+            }
+        }
+        Ok(Self { inputs, nodes })
+    }
+
+    /// Swaps the output wires of two gates. Nothing changes unless both wires are gate outputs.
+    fn swap(&mut self, src: &'a str, tgt: &'a str) {
+        if !(self.nodes.contains_key(src) && self.nodes.contains_key(tgt)) {
+            return;
+        }
+        let src_op = self.nodes.remove(src).expect("checked above");
+        let tgt_op = self.nodes.remove(tgt).expect("checked above");
         self.nodes.insert(src, tgt_op);
         self.nodes.insert(tgt, src_op);
     }
-}
 
-pub fn level1(input: &str) -> i64 {
-    let computer = Computer::from_input(input).expect("parse");
-    let results = computer.results();
-    results
-        .into_iter()
-        .filter(|(node, _)| node.starts_with('z'))
-        .sorted()
-        .rev()
-        .map(|(_, bit)| bit)
-        .fold(0, |acc, bit| 2 * acc + bit as i64)
-}
-
-pub fn level2(input: &str) -> i64 {
-    // check some properties, this is easier to solve ad hoc in the editor
-    let mut computer = Computer::from_input(input).expect("parse");
-    let swaps = [
-        ["tpk", "wkb"],
-        ["shj", "z07"],
-        ["pfn", "z23"],
-        ["kcd", "z27"],
-    ];
-    for swap in &swaps {
-        computer.swap(swap[0], swap[1]);
-    }
-    let bit_count = computer.inputs.len() / 2;
-
-    let xors = (0..bit_count)
-        .map(|i| {
-            let Some((label, _)) = computer.nodes.iter().find(|(_, (op, mut inputs))| {
-                let x_input = format!("x{i:02}");
-                let y_input = format!("y{i:02}");
-
-                inputs.sort();
-                matches!(op, Operation::Xor) && inputs == [x_input, y_input]
-            }) else {
-                panic!("No xor for bit {i:02}");
+    fn bit_count(&self) -> usize {
+        self.inputs.len() / 2
+    }
+
+    /// The output of the gate applying `operation` to both wires, in either order.
+    fn gate_output(&self, operation: Operation, left: &str, right: &str) -> Option<&'a str> {
+        self.nodes
+            .iter()
+            .find(|(_, (op, [a, b]))| {
+                *op == operation && ((*a, *b) == (left, right) || (*a, *b) == (right, left))
+            })
+            .map(|(output, _)| *output)
+    }
+
+    /// Follows the ripple-carry adder bit by bit: `z_i = (x_i XOR y_i) XOR c_i` and
+    /// `c_(i+1) = (x_i AND y_i) OR ((x_i XOR y_i) AND c_i)`, with `c_0` absent and the last
+    /// carry as the top output. Returns the lowest bit where a gate is missing or has the wrong
+    /// output wire, together with the wires touched while checking it.
+    fn first_faulty_bit(&self) -> Option<(usize, Vec<&'a str>)> {
+        let mut carry: Option<&'a str> = None;
+        for bit in 0..self.bit_count() {
+            let [x, y, z] = ["x", "y", "z"].map(|prefix| format!("{prefix}{bit:02}"));
+            let half_sum = self.gate_output(Operation::Xor, &x, &y);
+            let half_carry = self.gate_output(Operation::And, &x, &y);
+            let mut touched = [half_sum, half_carry, carry]
+                .into_iter()
+                .flatten()
+                .collect_vec();
+            let (Some(half_sum), Some(half_carry)) = (half_sum, half_carry) else {
+                return Some((bit, touched));
             };
-            *label
-        })
-        .collect_vec();
-    let ands = (0..bit_count)
-        .map(|i| {
-            let Some((label, _)) = computer.nodes.iter().find(|(_, (op, mut inputs))| {
-                let x_input = format!("x{i:02}");
-                let y_input = format!("y{i:02}");
-
-                inputs.sort();
-                matches!(op, Operation::And) && inputs == [x_input, y_input]
-            }) else {
-                panic!("No xor for bit {i:02}");
+            let Some(carry_in) = carry else {
+                if half_sum != z {
+                    return Some((bit, touched));
+                }
+                carry = Some(half_carry);
+                continue;
             };
-            *label
+            let sum = self.gate_output(Operation::Xor, half_sum, carry_in);
+            let propagated = self.gate_output(Operation::And, half_sum, carry_in);
+            touched.extend([sum, propagated].into_iter().flatten());
+            let carry_out = propagated
+                .and_then(|propagated| self.gate_output(Operation::Or, half_carry, propagated));
+            touched.extend(carry_out);
+            if sum != Some(z.as_str()) || carry_out.is_none() {
+                // The sum belongs on the output, so whatever sits there is suspect as well.
+                touched.extend(self.nodes.get_key_value(z.as_str()).map(|(wire, _)| *wire));
+                return Some((bit, touched));
+            }
+            carry = carry_out;
+        }
+        let top = format!("z{:02}", self.bit_count());
+        (carry != Some(top.as_str())).then(|| {
+            let touched = carry.into_iter().chain(
+                self.nodes
+                    .get_key_value(top.as_str())
+                    .map(|(wire, _)| *wire),
+            );
+            (self.bit_count(), touched.collect())
         })
-        .collect_vec();
+    }
 
-    let carries = (1..bit_count)
-        .map(|i| {
-            let Some(label) = computer
+    /// Repairs the adder by swapping pairs of gate outputs, fixing the lowest faulty bit each
+    /// time with a swap between wires involved in it or in the bit after it. Returns the swapped
+    /// wires, or `None` if some bit can't be repaired by a single swap.
+    fn repair_swaps(&mut self) -> Option<Vec<[&'a str; 2]>> {
+        let mut swaps = Vec::new();
+        while let Some((bit, mut candidates)) = self.first_faulty_bit() {
+            // Wires of the next bit are included, since the carry into it may be the culprit.
+            let readers = self
                 .nodes
                 .iter()
-                .flat_map(|(_, (op, [left, right]))| {
-                    let xor = xors[i];
-
-                    if matches!(op, Operation::Xor) {
-                        if *left == xor {
-                            Some(right)
-                        } else if *right == xor {
-                            Some(left)
-                        } else {
-                            None
-                        }
-                    } else {
-                        None
-                    }
-                })
-                .next()
-            else {
-                panic!("no xor carry operation for {i:02}");
-            };
-            *label
-        })
-        .collect_vec();
-
-    for i in 0..bit_count {
-        let (op, mut inputs) = computer
-            .nodes
-            .get(&format!("z{i:02}").as_str())
-            .copied()
-            .unwrap();
-        assert!(
-            matches!(op, Operation::Xor),
-            "unexpected operation {op:?} for bit {i:02}",
-        );
-        inputs.sort();
-
-        let known_inputs = if i == 0 {
-            ["x00", "y00"]
-        } else {
-            let mut known_inputs = [xors[i], carries[i - 1]];
-            known_inputs.sort();
-            known_inputs
-        };
-        assert_eq!(
-            inputs, known_inputs,
-            "unexpected inputs {inputs:?} for bit {i:02} (expected {known_inputs:?})"
-        );
+                .filter(|(_, (_, inputs))| inputs.iter().any(|input| candidates.contains(input)))
+                .map(|(output, _)| *output)
+                .collect_vec();
+            candidates.extend(readers);
+            candidates.sort_unstable();
+            candidates.dedup();
+            let swap = candidates
+                .iter()
+                .tuple_combinations()
+                .map(|(a, b)| [*a, *b])
+                .find(|[a, b]| {
+                    self.swap(a, b);
+                    let fixed = self
+                        .first_faulty_bit()
+                        .is_none_or(|(faulty, _)| faulty > bit);
+                    self.swap(a, b);
+                    fixed
+                })?;
+            self.swap(swap[0], swap[1]);
+            swaps.push(swap);
+        }
+        Some(swaps)
     }
+}
 
-    let output = swaps.into_iter().flatten().sorted().join(",");
-    println!("{output}");
-    0
+pub fn level1(input: &str) -> i64 {
+    let input = Input::new(input);
+    let computer = Computer::from_input(&input).expect("parse");
+    let results = computer.evaluate().unwrap_or_else(|e| panic!("{e}"));
+    Computer::read(&results, 'z') as i64
+}
+
+/// The circuit with the wires that break the adder structure highlighted.
+pub fn circuit_dot(input: &str) -> Dot {
+    let input = Input::new(input);
+    let computer = Computer::from_input(&input).expect("parse");
+    computer.to_dot(&computer.validate())
+}
+
+/// The circuit as a structural Verilog module.
+pub fn circuit_verilog(input: &str) -> String {
+    let input = Input::new(input);
+    Computer::from_input(&input).expect("parse").to_verilog()
+}
+
+/// The circuit in BLIF, with the input values in comments.
+pub fn circuit_blif(input: &str) -> String {
+    let input = Input::new(input);
+    Computer::from_input(&input).expect("parse").to_blif()
+}
+
+pub fn level2(input: &str) -> String {
+    let input = Input::new(input);
+    let mut computer = Computer::from_input(&input).expect("parse");
+    let swaps = computer
+        .repair_swaps()
+        .expect("the adder can be repaired by swaps");
+    if let Err(error) = computer.verify(100) {
+        panic!("the repaired circuit still fails: {error}");
+    }
+    swaps.into_iter().flatten().sorted().join(",")
 }
 
 #[cfg(test)]
@@ -236,6 +733,157 @@ mod test {
         assert_eq!(level1(test_input), 4)
     }
 
+    #[test]
+    fn validate_swapped_adder() {
+        let test_input = include_str!("./test_input/day24_adder_swapped.txt");
+        let input = Input::new(test_input);
+        let computer = Computer::from_input(&input).unwrap();
+        let flagged = computer.validate();
+        assert_eq!(flagged.iter().copied().collect_vec(), ["b01", "z01"]);
+        let dot = computer.to_dot(&flagged).to_string();
+        assert!(dot.contains(r#""b01" [label="b01\nXOR", shape="diamond", color="red"];"#));
+        assert!(dot.contains(r#"{ rank=sink; "z00"; "z01"; "z02"; }"#));
+    }
+
+    #[test]
+    fn repair_swapped_adder() {
+        let test_input = include_str!("./test_input/day24_adder_swapped.txt");
+        assert_eq!(level2(test_input), "b01,z01");
+        let test_input = include_str!("./test_input/day24_adder_two_swaps.txt");
+        assert_eq!(level2(test_input), "c03,h02,s02,z03");
+    }
+
+    #[test]
+    fn evaluation_and_verification() {
+        let test_input = include_str!("./test_input/day24_adder_two_swaps.txt");
+        let input = Input::new(test_input);
+        let mut computer = Computer::from_input(&input).unwrap();
+        assert_eq!(computer.evaluate(), Err(EvalError::Cycle(vec!["c03"])));
+        assert_eq!(
+            computer.verify(10),
+            Err(VerifyError::Eval(EvalError::Cycle(vec!["c03"])))
+        );
+        computer.swap("c03", "z03");
+        let Err(VerifyError::WrongSum { bit, .. }) = computer.verify(10) else {
+            panic!("bits 2 and 3 are still broken");
+        };
+        assert_eq!(bit, 2);
+        computer.swap("h02", "s02");
+        assert_eq!(computer.verify(50), Ok(()));
+        // Input wires and unknown wires are not gate outputs, so the gates stay put.
+        computer.swap("z03", "x00");
+        computer.swap("nope", "z03");
+        assert_eq!(computer.verify(50), Ok(()));
+        assert_eq!(computer.add(0b1011, 0b0110), Ok(0b10001));
+        assert_eq!(computer.add(15, 15), Ok(30));
+
+        let test_input = include_str!("./test_input/day24_adder_swapped.txt");
+        let input = Input::new(test_input);
+        let mut computer = Computer::from_input(&input).unwrap();
+        let error = computer.verify(0).unwrap_err();
+        assert_eq!(error.bit(), 1);
+        assert_eq!(
+            error.to_string(),
+            "bit 1 is wrong: 3 + 0 gave 5 instead of 3"
+        );
+    }
+
+    /// A correct ripple-carry adder over `bits` bits, with all inputs zero.
+    fn ripple_carry_adder(bits: usize) -> String {
+        let mut text = String::new();
+        for operand in ["x", "y"] {
+            for i in 0..bits {
+                text.push_str(&format!("{operand}{i:02}: 0\n"));
+            }
+        }
+        text.push('\n');
+        let carry = |i: usize| {
+            if i + 1 == bits {
+                format!("z{bits:02}")
+            } else {
+                format!("c{i:02}")
+            }
+        };
+        for i in 0..bits {
+            if i == 0 {
+                text.push_str(&format!(
+                    "x00 XOR y00 -> z00\nx00 AND y00 -> {}\n",
+                    carry(0)
+                ));
+                continue;
+            }
+            let previous = format!("c{:02}", i - 1);
+            text.push_str(&format!(
+                "x{i:02} XOR y{i:02} -> s{i:02}\nx{i:02} AND y{i:02} -> a{i:02}\n\
+                 s{i:02} XOR {previous} -> z{i:02}\ns{i:02} AND {previous} -> b{i:02}\n\
+                 a{i:02} OR b{i:02} -> {}\n",
+                carry(i)
+            ));
+        }
+        text
+    }
+
+    #[test]
+    fn wide_adders_and_unknown_inputs() {
+        let text = ripple_carry_adder(64);
+        let input = Input::new(&text);
+        let mut computer = Computer::from_input(&input).unwrap();
+        assert_eq!(computer.verify(20), Ok(()));
+        assert_eq!(computer.add(u64::MAX, 1), Ok(1 << 64));
+        assert_eq!(computer.add(u64::MAX, u64::MAX), Ok(2 * u64::MAX as u128));
+
+        let input =
+            Input::new("x00: 1\ny00: 0\nw00: 1\n\nx00 XOR y00 -> z00\nx00 AND w00 -> z01\n");
+        let mut computer = Computer::from_input(&input).unwrap();
+        assert_eq!(computer.add(1, 0), Err(EvalError::UnknownInput("w00")));
+    }
+
+    #[test]
+    fn netlist_exports() {
+        for test_input in [
+            include_str!("./test_input/day24.txt"),
+            include_str!("./test_input/day24_large.txt"),
+            include_str!("./test_input/day24_adder_swapped.txt"),
+        ] {
+            let input = Input::new(test_input);
+            let computer = Computer::from_input(&input).unwrap();
+            let blif = computer.to_blif();
+            let parsed = Computer::from_blif(&blif).unwrap();
+            assert_eq!(parsed, computer);
+            assert_eq!(parsed.evaluate(), computer.evaluate());
+        }
+
+        let test_input = include_str!("./test_input/day24_adder_swapped.txt");
+        let verilog = circuit_verilog(test_input);
+        assert!(verilog.starts_with("// input values: x00=1 x01=0 y00=1 y01=1\n"));
+        assert!(verilog.contains("module circuit(x00, x01, y00, y01, z00, z01, z02);\n"));
+        assert!(verilog.contains("  xor g_z00(z00, x00, y00);\n"));
+        assert!(verilog.ends_with("endmodule\n"));
+        assert!(circuit_blif(test_input).contains(".names x00 y00 z00\n01 1\n10 1\n"));
+    }
+
+    #[test]
+    fn blif_errors() {
+        let error = |blif| Computer::from_blif(blif).unwrap_err().to_string();
+        assert_eq!(
+            error(".model m\n.inputs a b\n.names a b c\n11 1\n00 1\n.end\n"),
+            "line 3: cover [\"11\", \"00\"] is not AND, OR or XOR"
+        );
+        assert_eq!(
+            error(".model m\n.latch a b\n"),
+            "line 2: unsupported directive \".latch\""
+        );
+        assert_eq!(
+            error(".names a b\n1 1\n"),
+            "line 1: gate has 1 inputs instead of two"
+        );
+        assert_eq!(error("11 1\n"), "line 1: cover row outside of .names");
+        assert_eq!(
+            error(".inputs a\n# a: 2\n"),
+            "line 2: invalid input value \"2\""
+        );
+    }
+
     #[test]
     fn level1_given_example_large() {
         let test_input = include_str!("./test_input/day24_large.txt");
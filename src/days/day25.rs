@@ -10,7 +10,8 @@ pub fn level1(input: &str) -> usize {
     let mut locks_by_cylinder_and_depth = [0; 5].map(|_| [0; 6].map(|_| BTreeSet::new()));
     let mut keys = Vec::new();
 
-    for chunk in input.trim().split("\n\n") {
+    for block in Input::new(input).blocks() {
+        let chunk = block.as_str().trim_end();
         if let Some(chunk) = chunk.strip_prefix("#####\n") {
             let chunk = chunk.strip_suffix("\n.....").expect("chunk is lock");
             lock_count += 1;
@@ -60,7 +61,7 @@ pub fn level1(input: &str) -> usize {
     result
 }
 
-pub fn level2(input: &str) -> usize {
+pub fn level2(_input: &str) -> usize {
     0
 }
 
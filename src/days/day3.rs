@@ -10,7 +10,7 @@ use nom_supreme::{error::ErrorTree, final_parser::final_parser, tag::complete::t
 use crate::util::prelude::*;
 
 pub fn level1(input: &str) -> u32 {
-    let instructions = parse_line(input).expect("parse");
+    let instructions = parse_line(Input::new(input).as_str()).expect("parse");
     instructions
         .into_iter()
         .map(|instruction| match instruction {
@@ -21,7 +21,7 @@ pub fn level1(input: &str) -> u32 {
 }
 
 pub fn level2(input: &str) -> u32 {
-    let instructions = parse_line(input).expect("parse");
+    let instructions = parse_line(Input::new(input).as_str()).expect("parse");
     let mut enabled = true;
     instructions
         .into_iter()
@@ -1,95 +1,52 @@
-use std::collections::{BTreeMap, BTreeSet, HashSet};
+use crate::util::{
+    bitgrid::{BitGrid, DirectionalBitGrid},
+    prelude::*,
+};
 
-use crate::util::prelude::*;
-
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Clone)]
 struct Map {
-    obstacles: HashSet<(usize, usize)>,
-    width: usize,
-    height: usize,
+    obstacles: BitGrid,
 }
 
 impl Map {
-    fn steps_till_escape(&self, mut pos: (usize, usize)) -> Option<HashSet<(usize, usize)>> {
-        #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-        enum Dir {
-            North,
-            East,
-            South,
-            West,
-        }
-        let mut dir = Dir::North;
-        let mut visited: HashSet<_> = std::iter::once(pos).collect();
-        let mut visited_dir: HashSet<_> = std::iter::once((pos, dir)).collect();
+    /// Walks the guard until they leave the map or end up in a loop, recording the visited
+    /// states in `seen`. It is cleared first so it can be reused between walks.
+    fn escapes(&self, mut pos: GridPos, seen: &mut DirectionalBitGrid) -> bool {
+        seen.clear();
+        let mut dir = Direction::Up;
+        seen.insert(pos, dir);
         loop {
-            let next_pos = match dir {
-                Dir::North => {
-                    let Some(y) = pos.1.checked_sub(1) else {
-                        return Some(visited);
-                    };
-                    (pos.0, y)
-                }
-                Dir::East => {
-                    let x = pos.0 + 1;
-                    if x >= self.width {
-                        return Some(visited);
-                    }
-                    (x, pos.1)
-                }
-                Dir::South => {
-                    let y = pos.1 + 1;
-                    if y >= self.height {
-                        return Some(visited);
-                    }
-                    (pos.0, y)
-                }
-                Dir::West => {
-                    let Some(x) = pos.0.checked_sub(1) else {
-                        return Some(visited);
-                    };
-                    (x, pos.1)
-                }
+            let Some(next_pos) = pos.step(dir) else {
+                return true;
             };
+            if !self.obstacles.in_bounds(&next_pos) {
+                return true;
+            }
             if self.obstacles.contains(&next_pos) {
-                dir = match dir {
-                    Dir::North => Dir::East,
-                    Dir::East => Dir::South,
-                    Dir::South => Dir::West,
-                    Dir::West => Dir::North,
-                }
+                dir = dir.turn_right();
             } else {
-                visited.insert(next_pos);
-                if !visited_dir.insert((next_pos, dir)) {
-                    return None;
+                if !seen.insert(next_pos, dir) {
+                    return false;
                 };
                 pos = next_pos;
             }
         }
     }
-}
-
-fn parse_input(input: &str) -> (Map, (usize, usize)) {
-    let mut pos = None;
-    let mut map = Map::default();
 
-    for (y, line) in input.lines().enumerate() {
-        for (x, c) in line.chars().enumerate() {
-            match c {
-                '.' => {}
-                '^' => pos = Some((x, y)),
-                '#' => {
-                    map.obstacles.insert((x, y));
-                }
-                _ => panic!("unexpected char"),
-            }
-            if x == 0 {
-                map.width += 1;
-            }
-        }
-        map.height += 1;
+    fn steps_till_escape(&self, pos: GridPos) -> Option<BitGrid> {
+        let mut seen = DirectionalBitGrid::new(self.obstacles.length, self.obstacles.height);
+        self.escapes(pos, &mut seen).then(|| seen.positions())
     }
+}
+
+fn parse_input(input: &str) -> (Map, GridPos) {
+    let grid = Input::new(input)
+        .grid(|c| matches!(c, '.' | '^' | '#').then_some(c))
+        .expect("parse");
+    let pos = grid.find(&'^').expect("No starting position found");
+    let obstacles = BitGrid::from_grid(&grid, |c| *c == '#');
 
-    (map, pos.expect("No starting position found"))
+    (Map { obstacles }, pos)
 }
 
 pub fn level1(input: &str) -> usize {
@@ -102,12 +59,23 @@ pub fn level2(input: &str) -> usize {
     let (map, start_pos) = parse_input(input);
     let visited = map.steps_till_escape(start_pos).unwrap();
     visited
+        .iter()
+        .filter(|pos| *pos != start_pos)
+        .collect_vec()
         .into_par_iter()
-        .filter(|pos| {
-            let mut map = map.clone();
-            map.obstacles.insert(*pos);
-            map.steps_till_escape(start_pos).is_none()
-        })
+        .map_init(
+            || {
+                let seen = DirectionalBitGrid::new(map.obstacles.length, map.obstacles.height);
+                (map.clone(), seen)
+            },
+            |(map, seen), pos| {
+                map.obstacles.insert(pos);
+                let is_loop = !map.escapes(start_pos, seen);
+                map.obstacles.remove(&pos);
+                is_loop
+            },
+        )
+        .filter(|is_loop| *is_loop)
         .count()
 }
 
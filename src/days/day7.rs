@@ -1,29 +1,24 @@
 use std::collections::HashSet;
 
 use nom::{
-    bytes::complete::tag,
-    character::complete::{newline, u64},
-    combinator::eof,
+    bytes::complete::tag, character::complete::u64, multi::separated_list1,
     sequence::separated_pair,
 };
-use nom_supreme::{
-    error::ErrorTree, final_parser::final_parser, multi::collect_separated_terminated,
-};
 
-use crate::util::prelude::*;
+use crate::util::{input::lines_of, prelude::*};
+
+fn parse_equations(input: &Input) -> Vec<(u64, Vec<u64>)> {
+    input
+        .parse(lines_of(separated_pair(
+            u64,
+            tag(": "),
+            separated_list1(tag(" "), u64),
+        )))
+        .expect("parse error")
+}
 
 pub fn level1(input: &str) -> u64 {
-    let parsed: Result<Vec<(_, Vec<_>)>, ErrorTree<&str>> =
-        final_parser::<_, _, ErrorTree<&str>, _>(collect_separated_terminated(
-            separated_pair(
-                u64,
-                tag(": "),
-                collect_separated_terminated(u64, tag(" "), newline),
-            ),
-            tag(""),
-            eof,
-        ))(input);
-    let parsed = parsed.expect("parse error");
+    let parsed = parse_equations(&Input::new(input));
     parsed
         .into_iter()
         .filter(|(target, numbers)| can_build(*target, numbers))
@@ -76,17 +71,7 @@ fn can_build_with_concatenate(target: u64, numbers: &[u64]) -> bool {
     candidates.into_iter().any(|x| x == target)
 }
 pub fn level2(input: &str) -> u64 {
-    let parsed: Result<Vec<(_, Vec<_>)>, ErrorTree<&str>> =
-        final_parser::<_, _, ErrorTree<&str>, _>(collect_separated_terminated(
-            separated_pair(
-                u64,
-                tag(": "),
-                collect_separated_terminated(u64, tag(" "), newline),
-            ),
-            tag(""),
-            eof,
-        ))(input);
-    let parsed = parsed.expect("parse error");
+    let parsed = parse_equations(&Input::new(input));
     parsed
         .into_iter()
         .filter(|(target, numbers)| can_build_with_concatenate(*target, numbers))
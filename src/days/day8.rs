@@ -1,23 +1,6 @@
 use std::collections::{HashMap, HashSet};
 
-use crate::util::prelude::*;
-
-fn gcd(mut x: usize, mut y: usize) -> usize {
-    let bits = x.trailing_zeros().min(y.trailing_zeros());
-    x >>= x.trailing_zeros();
-    y >>= y.trailing_zeros();
-
-    if x > y {
-        std::mem::swap(&mut y, &mut x);
-    }
-    while x > 0 {
-        x >>= x.trailing_zeros();
-        let z = x;
-        x = y.abs_diff(x);
-        y = z;
-    }
-    y << bits
-}
+use crate::util::{math::gcd, prelude::*};
 
 pub fn level1(input: &str) -> usize {
     let (map, width, height) = parse_input(input);
@@ -53,22 +36,15 @@ pub fn level1(input: &str) -> usize {
 type AntennaMap = (HashMap<char, Vec<(usize, usize)>>, usize, usize);
 
 fn parse_input(input: &str) -> AntennaMap {
-    let mut map: HashMap<char, Vec<(usize, usize)>> = HashMap::new();
-    let mut width = 0;
-    let mut height = 0;
-
-    for (y, line) in input.lines().enumerate() {
-        for (x, c) in line.chars().enumerate() {
-            if y == 0 {
-                width = x + 1;
-            }
-            if c != '.' {
-                map.entry(c).or_default().push((x, y))
-            };
-            height = y + 1;
-        }
-    }
-    (map, width, height)
+    let grid = Input::new(input)
+        .grid(|c| (c == '.' || c.is_ascii_alphanumeric()).then_some(c))
+        .expect("parse");
+    let map = grid
+        .enumerate()
+        .filter(|(_, c)| **c != '.')
+        .map(|(pos, c)| (*c, (pos.x, pos.y)))
+        .into_group_map();
+    (map, grid.length, grid.height)
 }
 
 pub fn level2(input: &str) -> usize {
@@ -82,7 +58,7 @@ pub fn level2(input: &str) -> usize {
                 second.0 as isize - first.0 as isize,
                 second.1 as isize - first.1 as isize,
             );
-            let d = gcd(delta.0.unsigned_abs(), delta.1.unsigned_abs()) as isize;
+            let d = gcd(delta.0.unsigned_abs() as u64, delta.1.unsigned_abs() as u64) as isize;
             let step = (delta.0 / d, delta.1 / d);
             antinodes.extend(
                 (1..)
@@ -5,10 +5,11 @@ use crate::util::prelude::*;
 
 pub fn level1(input: &str) -> usize {
     let mut running_length = 0;
-    let (mut file_ids_and_lengths, mut gaps): (Vec<_>, Vec<_>) = input
+    let (mut file_ids_and_lengths, mut gaps): (Vec<_>, Vec<_>) = Input::new(input)
+        .as_str()
         .trim_end()
         .chars()
-        .map(|c| (c.to_digit(10).expect("not a digit") as usize))
+        .map(|c| c.to_digit(10).expect("not a digit") as usize)
         .chunks(2)
         .into_iter()
         .enumerate()
@@ -51,10 +52,11 @@ pub fn level1(input: &str) -> usize {
 
 pub fn level2(input: &str) -> usize {
     let mut running_length = 0;
-    let (file_ids_and_lengths, mut gaps): (Vec<_>, Vec<_>) = input
+    let (file_ids_and_lengths, mut gaps): (Vec<_>, Vec<_>) = Input::new(input)
+        .as_str()
         .trim_end()
         .chars()
-        .map(|c| (c.to_digit(10).expect("not a digit") as usize))
+        .map(|c| c.to_digit(10).expect("not a digit") as usize)
         .chunks(2)
         .into_iter()
         .enumerate()
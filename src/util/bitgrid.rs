@@ -0,0 +1,167 @@
+use bitvec::vec::BitVec;
+
+use super::prelude::*;
+
+/// A set of positions in a fixed-size grid, one bit per cell.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitGrid {
+    bits: BitVec,
+    pub length: usize,
+    pub height: usize,
+}
+
+impl BitGrid {
+    pub fn new(length: usize, height: usize) -> Self {
+        Self {
+            bits: BitVec::repeat(false, length * height),
+            length,
+            height,
+        }
+    }
+
+    /// The set of cells of `grid` for which `include` holds.
+    pub fn from_grid<T>(grid: &Grid<T>, mut include: impl FnMut(&T) -> bool) -> Self {
+        Self {
+            bits: grid.iter().map(&mut include).collect(),
+            length: grid.length,
+            height: grid.height,
+        }
+    }
+
+    fn index(&self, pos: &GridPos) -> Option<usize> {
+        (pos.x < self.length && pos.y < self.height).then_some(pos.y * self.length + pos.x)
+    }
+
+    /// Whether `pos` lies inside the grid.
+    pub fn in_bounds(&self, pos: &GridPos) -> bool {
+        self.index(pos).is_some()
+    }
+
+    /// Whether `pos` is set, `false` outside of the grid.
+    pub fn contains(&self, pos: &GridPos) -> bool {
+        self.index(pos).is_some_and(|index| self.bits[index])
+    }
+
+    /// Sets `pos` and returns whether it was unset before. Panics outside of the grid.
+    pub fn insert(&mut self, pos: GridPos) -> bool {
+        let index = self.index(&pos).expect("position outside of grid");
+        !self.bits.replace(index, true)
+    }
+
+    /// Unsets `pos` and returns whether it was set before.
+    pub fn remove(&mut self, pos: &GridPos) -> bool {
+        match self.index(pos) {
+            Some(index) => self.bits.replace(index, false),
+            None => false,
+        }
+    }
+
+    /// Unsets all cells without reallocating.
+    pub fn clear(&mut self) {
+        self.bits.fill(false);
+    }
+
+    pub fn len(&self) -> usize {
+        self.bits.count_ones()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits.not_any()
+    }
+
+    /// The set positions in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = GridPos> + '_ {
+        self.bits.iter_ones().map(|index| GridPos {
+            x: index % self.length,
+            y: index / self.length,
+        })
+    }
+}
+
+/// A set of `(position, direction)` states in a fixed-size grid, four bits per cell.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectionalBitGrid {
+    bits: BitVec,
+    pub length: usize,
+    pub height: usize,
+}
+
+impl DirectionalBitGrid {
+    pub fn new(length: usize, height: usize) -> Self {
+        Self {
+            bits: BitVec::repeat(false, 4 * length * height),
+            length,
+            height,
+        }
+    }
+
+    fn index(&self, pos: &GridPos, direction: Direction) -> Option<usize> {
+        (pos.x < self.length && pos.y < self.height)
+            .then_some(4 * (pos.y * self.length + pos.x) + direction as usize)
+    }
+
+    /// Whether the state is set, `false` outside of the grid.
+    pub fn contains(&self, pos: &GridPos, direction: Direction) -> bool {
+        self.index(pos, direction)
+            .is_some_and(|index| self.bits[index])
+    }
+
+    /// Sets the state and returns whether it was unset before. Panics outside of the grid.
+    pub fn insert(&mut self, pos: GridPos, direction: Direction) -> bool {
+        let index = self
+            .index(&pos, direction)
+            .expect("position outside of grid");
+        !self.bits.replace(index, true)
+    }
+
+    /// Unsets all states without reallocating.
+    pub fn clear(&mut self) {
+        self.bits.fill(false);
+    }
+
+    /// The positions that are set in any direction.
+    pub fn positions(&self) -> BitGrid {
+        BitGrid {
+            bits: self.bits.chunks(4).map(|cell| cell.any()).collect(),
+            length: self.length,
+            height: self.height,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn set_operations() {
+        let mut grid = BitGrid::new(3, 2);
+        let pos = GridPos { x: 2, y: 1 };
+        assert!(grid.insert(pos));
+        assert!(!grid.insert(pos));
+        assert!(grid.contains(&pos));
+        assert!(!grid.contains(&GridPos { x: 3, y: 0 }));
+        assert_eq!(grid.iter().collect_vec(), vec![pos]);
+        assert!(grid.remove(&pos));
+        assert!(grid.is_empty());
+
+        let walls = Grid::from_str("#.#\n..#\n", |c| Some(c == '#')).unwrap();
+        let mut grid = BitGrid::from_grid(&walls, |wall| *wall);
+        assert_eq!(grid.len(), 3);
+        grid.clear();
+        assert_eq!(grid.len(), 0);
+    }
+
+    #[test]
+    fn directional() {
+        let mut grid = DirectionalBitGrid::new(2, 2);
+        let pos = GridPos { x: 1, y: 0 };
+        assert!(grid.insert(pos, Direction::Left));
+        assert!(!grid.insert(pos, Direction::Left));
+        assert!(grid.insert(pos, Direction::Up));
+        assert!(!grid.contains(&pos, Direction::Down));
+        assert_eq!(grid.positions().iter().collect_vec(), vec![pos]);
+        grid.clear();
+        assert!(!grid.contains(&pos, Direction::Left));
+    }
+}
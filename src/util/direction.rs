@@ -0,0 +1,220 @@
+use super::prelude::GridPos;
+
+/// One of the four axis-aligned directions, with `Up` pointing towards smaller `y`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    /// All directions in clockwise order, starting with `Up`.
+    pub const ALL: [Self; 4] = [Self::Up, Self::Right, Self::Down, Self::Left];
+
+    pub fn turn_right(self) -> Self {
+        match self {
+            Self::Up => Self::Right,
+            Self::Right => Self::Down,
+            Self::Down => Self::Left,
+            Self::Left => Self::Up,
+        }
+    }
+
+    pub fn turn_left(self) -> Self {
+        self.turn_right().opposite()
+    }
+
+    pub fn opposite(self) -> Self {
+        match self {
+            Self::Up => Self::Down,
+            Self::Right => Self::Left,
+            Self::Down => Self::Up,
+            Self::Left => Self::Right,
+        }
+    }
+
+    pub fn delta(self) -> [isize; 2] {
+        Heading::from(self).delta()
+    }
+}
+
+impl TryFrom<char> for Direction {
+    type Error = String;
+
+    /// Parses the arrows `^>v<`.
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        match value {
+            '^' => Ok(Self::Up),
+            '>' => Ok(Self::Right),
+            'v' => Ok(Self::Down),
+            '<' => Ok(Self::Left),
+            c => Err(format!("unexpected character: {c}")),
+        }
+    }
+}
+
+impl From<Direction> for char {
+    fn from(value: Direction) -> Self {
+        match value {
+            Direction::Up => '^',
+            Direction::Right => '>',
+            Direction::Down => 'v',
+            Direction::Left => '<',
+        }
+    }
+}
+
+/// One of the eight compass headings, i.e. a [`Direction`] or a diagonal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Heading {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Heading {
+    /// All headings in clockwise order, starting with `North`.
+    pub const ALL: [Self; 8] = [
+        Self::North,
+        Self::NorthEast,
+        Self::East,
+        Self::SouthEast,
+        Self::South,
+        Self::SouthWest,
+        Self::West,
+        Self::NorthWest,
+    ];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Turns clockwise by 45 degrees.
+    pub fn turn_right(self) -> Self {
+        Self::ALL[(self.index() + 1) % 8]
+    }
+
+    /// Turns counterclockwise by 45 degrees.
+    pub fn turn_left(self) -> Self {
+        Self::ALL[(self.index() + 7) % 8]
+    }
+
+    pub fn opposite(self) -> Self {
+        Self::ALL[(self.index() + 4) % 8]
+    }
+
+    pub fn is_diagonal(self) -> bool {
+        self.index() % 2 == 1
+    }
+
+    pub fn delta(self) -> [isize; 2] {
+        match self {
+            Self::North => [0, -1],
+            Self::NorthEast => [1, -1],
+            Self::East => [1, 0],
+            Self::SouthEast => [1, 1],
+            Self::South => [0, 1],
+            Self::SouthWest => [-1, 1],
+            Self::West => [-1, 0],
+            Self::NorthWest => [-1, -1],
+        }
+    }
+}
+
+impl From<Direction> for Heading {
+    fn from(value: Direction) -> Self {
+        match value {
+            Direction::Up => Self::North,
+            Direction::Right => Self::East,
+            Direction::Down => Self::South,
+            Direction::Left => Self::West,
+        }
+    }
+}
+
+impl TryFrom<Heading> for Direction {
+    type Error = Heading;
+
+    /// Fails for diagonal headings, returning them unchanged.
+    fn try_from(value: Heading) -> Result<Self, Self::Error> {
+        match value {
+            Heading::North => Ok(Self::Up),
+            Heading::East => Ok(Self::Right),
+            Heading::South => Ok(Self::Down),
+            Heading::West => Ok(Self::Left),
+            diagonal => Err(diagonal),
+        }
+    }
+}
+
+impl GridPos {
+    /// The neighbouring position, or `None` if it would have a negative coordinate.
+    pub fn step(self, heading: impl Into<Heading>) -> Option<Self> {
+        self.step_by(heading, 1)
+    }
+
+    /// Moves `distance` cells in the given heading, if that stays in the first quadrant.
+    pub fn step_by(self, heading: impl Into<Heading>, distance: usize) -> Option<Self> {
+        let [dx, dy] = heading.into().delta();
+        let distance = isize::try_from(distance).ok()?;
+        Some(Self {
+            x: self.x.checked_add_signed(dx.checked_mul(distance)?)?,
+            y: self.y.checked_add_signed(dy.checked_mul(distance)?)?,
+        })
+    }
+
+    /// The neighbouring position, if it lies in a grid of the given size.
+    pub fn step_within(
+        self,
+        heading: impl Into<Heading>,
+        length: usize,
+        height: usize,
+    ) -> Option<Self> {
+        self.step(heading)
+            .filter(|pos| pos.x < length && pos.y < height)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn turns() {
+        for direction in Direction::ALL {
+            assert_eq!(direction.turn_right().turn_left(), direction);
+            assert_eq!(direction.turn_right().turn_right(), direction.opposite());
+            assert_eq!(
+                Heading::from(direction).turn_right().turn_right(),
+                Heading::from(direction.turn_right())
+            );
+            let c: char = direction.into();
+            assert_eq!(Direction::try_from(c), Ok(direction));
+        }
+        for heading in Heading::ALL {
+            let [dx, dy] = heading.delta();
+            assert_eq!(heading.opposite().delta(), [-dx, -dy]);
+            assert_eq!(heading.is_diagonal(), Direction::try_from(heading).is_err());
+        }
+    }
+
+    #[test]
+    fn stepping() {
+        let pos = GridPos { x: 0, y: 1 };
+        assert_eq!(pos.step(Direction::Up), Some(GridPos { x: 0, y: 0 }));
+        assert_eq!(pos.step(Direction::Left), None);
+        assert_eq!(pos.step(Heading::SouthEast), Some(GridPos { x: 1, y: 2 }));
+        assert_eq!(
+            pos.step_by(Direction::Right, 3),
+            Some(GridPos { x: 3, y: 1 })
+        );
+        assert_eq!(pos.step_within(Direction::Down, 1, 2), None);
+    }
+}
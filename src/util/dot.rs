@@ -0,0 +1,126 @@
+use std::{fmt::Display, io, path::Path};
+
+/// A Graphviz graph in the DOT language, built up statement by statement.
+#[derive(Debug, Clone)]
+pub struct Dot {
+    name: String,
+    directed: bool,
+    statements: Vec<String>,
+}
+
+/// Quotes an identifier or attribute value. Backslashes are kept, so labels can use DOT
+/// escapes like `\n`.
+fn quote(value: impl Display) -> String {
+    format!("\"{}\"", value.to_string().replace('"', "\\\""))
+}
+
+fn attribute_list(attributes: &[(&str, &str)]) -> String {
+    if attributes.is_empty() {
+        return String::new();
+    }
+    let attributes = attributes
+        .iter()
+        .map(|(key, value)| format!("{key}={}", quote(value)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(" [{attributes}]")
+}
+
+impl Dot {
+    pub fn digraph(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            directed: true,
+            statements: Vec::new(),
+        }
+    }
+
+    pub fn graph(name: &str) -> Self {
+        Self {
+            directed: false,
+            ..Self::digraph(name)
+        }
+    }
+
+    /// Sets an attribute of the whole graph, such as `rankdir`.
+    pub fn attribute(&mut self, key: &str, value: &str) -> &mut Self {
+        self.statements.push(format!("{key}={};", quote(value)));
+        self
+    }
+
+    /// Sets a default attribute for all following nodes.
+    pub fn node_default(&mut self, key: &str, value: &str) -> &mut Self {
+        let attributes = attribute_list(&[(key, value)]);
+        self.statements.push(format!("node{attributes};"));
+        self
+    }
+
+    pub fn node(&mut self, id: impl Display, attributes: &[(&str, &str)]) -> &mut Self {
+        let attributes = attribute_list(attributes);
+        self.statements.push(format!("{}{attributes};", quote(id)));
+        self
+    }
+
+    pub fn edge(
+        &mut self,
+        from: impl Display,
+        to: impl Display,
+        attributes: &[(&str, &str)],
+    ) -> &mut Self {
+        let arrow = if self.directed { "->" } else { "--" };
+        let attributes = attribute_list(attributes);
+        self.statements.push(format!(
+            "{} {arrow} {}{attributes};",
+            quote(from),
+            quote(to)
+        ));
+        self
+    }
+
+    /// Places the nodes on one rank, e.g. `same`, `source` or `sink`.
+    pub fn rank<I: Display>(&mut self, rank: &str, ids: impl IntoIterator<Item = I>) -> &mut Self {
+        let ids = ids
+            .into_iter()
+            .map(|id| format!(" {};", quote(id)))
+            .collect::<String>();
+        self.statements.push(format!("{{ rank={rank};{ids} }}"));
+        self
+    }
+
+    pub fn write(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::write(path, self.to_string())
+    }
+}
+
+impl Display for Dot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = if self.directed { "digraph" } else { "graph" };
+        writeln!(f, "{kind} {} {{", quote(&self.name))?;
+        for statement in &self.statements {
+            writeln!(f, "    {statement}")?;
+        }
+        writeln!(f, "}}")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn render() {
+        let mut dot = Dot::digraph("g");
+        dot.attribute("rankdir", "LR")
+            .node("a \"1\"", &[("color", "red")])
+            .edge("a \"1\"", "b", &[])
+            .rank("same", ["a \"1\"", "b"]);
+        assert_eq!(
+            dot.to_string(),
+            "digraph \"g\" {\n    rankdir=\"LR\";\n    \"a \\\"1\\\"\" [color=\"red\"];\n    \
+             \"a \\\"1\\\"\" -> \"b\";\n    { rank=same; \"a \\\"1\\\"\"; \"b\"; }\n}\n"
+        );
+        let mut undirected = Dot::graph("h");
+        undirected.edge(1, 2, &[]);
+        assert!(undirected.to_string().contains("\"1\" -- \"2\";"));
+    }
+}
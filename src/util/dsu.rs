@@ -0,0 +1,245 @@
+use std::{collections::HashMap, hash::Hash};
+
+/// A disjoint-set forest over `0..len` with union by rank and path compression.
+#[derive(Debug, Clone, Default)]
+pub struct Dsu {
+    parent: Vec<usize>,
+    rank: Vec<u8>,
+    size: Vec<usize>,
+    components: usize,
+}
+
+impl Dsu {
+    /// `len` singleton sets.
+    pub fn new(len: usize) -> Self {
+        Self {
+            parent: (0..len).collect(),
+            rank: vec![0; len],
+            size: vec![1; len],
+            components: len,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    /// Adds a new singleton set and returns its element.
+    pub fn add(&mut self) -> usize {
+        let element = self.parent.len();
+        self.parent.push(element);
+        self.rank.push(0);
+        self.size.push(1);
+        self.components += 1;
+        element
+    }
+
+    /// The representative of the set containing `x`, without modifying the forest.
+    pub fn root(&self, mut x: usize) -> usize {
+        while self.parent[x] != x {
+            x = self.parent[x];
+        }
+        x
+    }
+
+    /// The representative of the set containing `x`, pointing every element on the way
+    /// directly at it.
+    pub fn find(&mut self, x: usize) -> usize {
+        let root = self.root(x);
+        let mut x = x;
+        while x != root {
+            x = std::mem::replace(&mut self.parent[x], root);
+        }
+        root
+    }
+
+    /// Merges the sets containing `a` and `b`. Returns whether they were separate before.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        let (parent, child) = if self.rank[a] < self.rank[b] {
+            (b, a)
+        } else {
+            (a, b)
+        };
+        self.parent[child] = parent;
+        self.size[parent] += self.size[child];
+        if self.rank[parent] == self.rank[child] {
+            self.rank[parent] += 1;
+        }
+        self.components -= 1;
+        true
+    }
+
+    pub fn same(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    /// The number of elements in the set containing `x`.
+    pub fn size(&self, x: usize) -> usize {
+        self.size[self.root(x)]
+    }
+
+    /// The number of disjoint sets.
+    pub fn component_count(&self) -> usize {
+        self.components
+    }
+
+    /// The elements of each set, in the order of their smallest element.
+    pub fn components(&self) -> impl Iterator<Item = Vec<usize>> {
+        let mut members = vec![Vec::new(); self.len()];
+        let mut order = Vec::with_capacity(self.components);
+        for x in 0..self.len() {
+            let root = self.root(x);
+            if members[root].is_empty() {
+                order.push(root);
+            }
+            members[root].push(x);
+        }
+        order
+            .into_iter()
+            .map(move |root| std::mem::take(&mut members[root]))
+    }
+}
+
+/// A disjoint-set forest over arbitrary hashable keys, which are added on first use.
+#[derive(Debug, Clone)]
+pub struct KeyedDsu<K> {
+    dsu: Dsu,
+    keys: Vec<K>,
+    indices: HashMap<K, usize>,
+}
+
+impl<K> Default for KeyedDsu<K> {
+    fn default() -> Self {
+        Self {
+            dsu: Dsu::default(),
+            keys: Vec::new(),
+            indices: HashMap::new(),
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone> KeyedDsu<K> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    pub fn contains(&self, key: &K) -> bool {
+        self.indices.contains_key(key)
+    }
+
+    /// Adds `key` as a singleton set unless it is present already, and returns its index.
+    pub fn insert(&mut self, key: K) -> usize {
+        if let Some(index) = self.indices.get(&key) {
+            return *index;
+        }
+        let index = self.dsu.add();
+        self.keys.push(key.clone());
+        self.indices.insert(key, index);
+        index
+    }
+
+    /// The representative of the set containing `key`, if present, without modifying the forest.
+    pub fn root(&self, key: &K) -> Option<&K> {
+        let index = self.indices.get(key)?;
+        Some(&self.keys[self.dsu.root(*index)])
+    }
+
+    /// The representative of the set containing `key`, if present.
+    pub fn find(&mut self, key: &K) -> Option<&K> {
+        let index = *self.indices.get(key)?;
+        Some(&self.keys[self.dsu.find(index)])
+    }
+
+    /// Merges the sets containing `a` and `b`, adding either key if missing. Returns whether
+    /// they were separate before.
+    pub fn union(&mut self, a: K, b: K) -> bool {
+        let a = self.insert(a);
+        let b = self.insert(b);
+        self.dsu.union(a, b)
+    }
+
+    /// Whether both keys are present and in the same set.
+    pub fn same(&mut self, a: &K, b: &K) -> bool {
+        match (self.indices.get(a), self.indices.get(b)) {
+            (Some(a), Some(b)) => {
+                let (a, b) = (*a, *b);
+                self.dsu.same(a, b)
+            }
+            _ => false,
+        }
+    }
+
+    /// The number of keys in the set containing `key`, or zero if it is missing.
+    pub fn size(&self, key: &K) -> usize {
+        self.indices
+            .get(key)
+            .map_or(0, |index| self.dsu.size(*index))
+    }
+
+    pub fn component_count(&self) -> usize {
+        self.dsu.component_count()
+    }
+
+    /// The keys of each set, in insertion order of their first key.
+    pub fn components(&self) -> impl Iterator<Item = Vec<&K>> {
+        self.dsu
+            .components()
+            .map(|component| component.into_iter().map(|i| &self.keys[i]).collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn union_and_sizes() {
+        let mut dsu = Dsu::new(6);
+        assert!(dsu.union(0, 1));
+        assert!(dsu.union(2, 3));
+        assert!(dsu.union(1, 3));
+        assert!(!dsu.union(0, 2));
+        assert!(dsu.same(0, 3));
+        assert!(!dsu.same(0, 4));
+        assert_eq!(dsu.size(2), 4);
+        assert_eq!(dsu.size(5), 1);
+        assert_eq!(dsu.component_count(), 3);
+        let extra = dsu.add();
+        dsu.union(extra, 4);
+        let components: Vec<_> = dsu.components().collect();
+        assert_eq!(components, [vec![0, 1, 2, 3], vec![4, 6], vec![5]]);
+    }
+
+    #[test]
+    fn keyed() {
+        let mut dsu = KeyedDsu::new();
+        dsu.insert("lonely");
+        dsu.union("a", "b");
+        dsu.union("c", "b");
+        assert!(dsu.same(&"a", &"c"));
+        assert!(!dsu.same(&"a", &"lonely"));
+        assert!(!dsu.same(&"a", &"missing"));
+        assert_eq!(dsu.size(&"c"), 3);
+        assert_eq!(dsu.size(&"missing"), 0);
+        assert_eq!(dsu.root(&"a"), dsu.root(&"c"));
+        assert_eq!(dsu.find(&"missing"), None);
+        let components: Vec<_> = dsu.components().collect();
+        assert_eq!(components, [vec![&"lonely"], vec![&"a", &"b", &"c"]]);
+    }
+}
@@ -0,0 +1,817 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    fmt::{Debug, Display},
+    hash::Hash,
+    ops::Range,
+};
+
+use bitvec::vec::BitVec;
+use itertools::multiunzip;
+
+use super::{dot::Dot, prelude::*};
+
+/// A directed graph in compressed sparse row form. Nodes are addressed by their index in
+/// [`DirectedGraph::nodes`], which [`DirectedGraph::index_of`] looks up in constant time.
+#[derive(Debug, Clone)]
+pub struct DirectedGraph<N> {
+    adjacency: Vec<usize>,
+    adjacency_offsets: Vec<usize>,
+    nodes: Vec<N>,
+    node_positions: HashMap<N, usize>,
+}
+
+impl<K> Default for DirectedGraph<K> {
+    fn default() -> Self {
+        Self {
+            adjacency: Default::default(),
+            adjacency_offsets: Default::default(),
+            nodes: Default::default(),
+            node_positions: Default::default(),
+        }
+    }
+}
+
+/// Collects edges and nodes before freezing them into a [`DirectedGraph`].
+#[derive(Debug, Clone)]
+pub struct DirectedGraphBuilder<N> {
+    edges: Vec<Vec<usize>>,
+    node_positions: HashMap<N, usize>,
+}
+
+impl<N> Default for DirectedGraphBuilder<N> {
+    fn default() -> Self {
+        Self {
+            edges: Default::default(),
+            node_positions: Default::default(),
+        }
+    }
+}
+
+impl<N> DirectedGraph<N>
+where
+    N: Hash + Eq,
+{
+    pub fn into_builder(mut self) -> DirectedGraphBuilder<N> {
+        DirectedGraphBuilder {
+            node_positions: self.node_positions,
+            edges: {
+                let mut edges_rev = self
+                    .adjacency_offsets
+                    .iter()
+                    .rev()
+                    .map(|ix| self.adjacency.split_off(*ix))
+                    .collect_vec();
+                edges_rev.reverse();
+                edges_rev
+            },
+        }
+    }
+
+    /// The index of `node`, if it is part of the graph.
+    pub fn index_of(&self, node: &N) -> Option<usize> {
+        self.node_positions.get(node).copied()
+    }
+
+    /// Whether the nodes appear in an order that no edge points backwards in. Nodes that are
+    /// not part of the graph make the order invalid.
+    pub fn is_sub_topological_order<'a>(&self, nodes: impl IntoIterator<Item = &'a N>) -> bool
+    where
+        N: 'a,
+    {
+        let mut visited = vec![false; self.nodes.len()];
+        for node in nodes {
+            let Some(node_ix) = self.index_of(node) else {
+                return false;
+            };
+            if self
+                .children(node_ix)
+                .iter()
+                .any(|child_ix| visited[*child_ix])
+            {
+                return false;
+            }
+            visited[node_ix] = true;
+        }
+        true
+    }
+
+    /// Whether there is a path from `from` to `to`, which includes `from == to`.
+    pub fn is_reachable(&self, from: &N, to: &N) -> bool {
+        match (self.index_of(from), self.index_of(to)) {
+            (Some(from), Some(to)) => self.reachable([from])[to],
+            _ => false,
+        }
+    }
+}
+
+impl<N> DirectedGraphBuilder<N>
+where
+    N: Hash + Eq,
+{
+    fn position(&mut self, node: N) -> usize {
+        use std::collections::hash_map::Entry::*;
+        match self.node_positions.entry(node) {
+            Occupied(occupied_entry) => *occupied_entry.get(),
+            Vacant(vacant_entry) => {
+                self.edges.push(Vec::new());
+                *vacant_entry.insert(self.edges.len() - 1)
+            }
+        }
+    }
+
+    /// Adds a node without edges, unless it is already present.
+    pub fn add_node(&mut self, node: N) -> usize {
+        self.position(node)
+    }
+
+    pub fn add_edge(&mut self, start: N, end: N) {
+        let ix_start = self.position(start);
+        let ix_end = self.position(end);
+        self.edges[ix_start].push(ix_end);
+    }
+
+    pub fn build(self) -> DirectedGraph<N>
+    where
+        N: Clone,
+    {
+        let mut running_offset: usize = 0;
+        DirectedGraph {
+            adjacency_offsets: self
+                .edges
+                .iter()
+                .map(|children| {
+                    let old_offset = running_offset;
+                    running_offset += children.len();
+                    old_offset
+                })
+                .collect(),
+            adjacency: self.edges.into_iter().flatten().collect(),
+
+            nodes: self
+                .node_positions
+                .iter()
+                .sorted_by_key(|(_, i)| **i)
+                .map(|(node, _)| node.clone())
+                .collect(),
+            node_positions: self.node_positions,
+        }
+    }
+
+    /// The graph induced by `nodes`, which are renumbered in the given order.
+    pub fn subgraph(&self, nodes: impl IntoIterator<Item = N>) -> DirectedGraphBuilder<N> {
+        let (node_positions, mut edges, old_to_new): (_, Vec<_>, HashMap<_, _>) =
+            multiunzip(nodes.into_iter().enumerate().map(|(i, node)| {
+                let node_ix = *self.node_positions.get(&node).expect("Node not in graph");
+                let edges = self.edges[node_ix].clone();
+
+                ((node, i), edges, (node_ix, i))
+            }));
+        for node_edges in &mut edges {
+            *node_edges = node_edges
+                .iter()
+                .flat_map(|old_ix| old_to_new.get(old_ix).copied())
+                .collect();
+        }
+
+        DirectedGraphBuilder {
+            node_positions,
+            edges,
+        }
+    }
+}
+
+impl<N> Extend<(N, N)> for DirectedGraphBuilder<N>
+where
+    N: Hash + Eq,
+{
+    fn extend<T: IntoIterator<Item = (N, N)>>(&mut self, iter: T) {
+        for (start, end) in iter {
+            self.add_edge(start, end);
+        }
+    }
+}
+
+impl<N> DirectedGraph<N> {
+    pub fn nodes(&self) -> &[N] {
+        &self.nodes
+    }
+
+    pub fn node(&self, node_ix: usize) -> &N {
+        &self.nodes[node_ix]
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn children(&self, node_ix: usize) -> &[usize] {
+        let start = self.adjacency_offsets[node_ix];
+        let end = self
+            .adjacency_offsets
+            .get(node_ix + 1)
+            .copied()
+            .unwrap_or(self.adjacency.len());
+        &self.adjacency[start..end]
+    }
+
+    /// All edges as pairs of node indices.
+    pub fn edges(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (0..self.nodes.len())
+            .flat_map(|start| self.children(start).iter().map(move |end| (start, *end)))
+    }
+
+    /// The same graph with every edge pointing the other way. Node indices are kept.
+    pub fn reverse(&self) -> Self
+    where
+        N: Clone,
+    {
+        let mut edges = vec![Vec::new(); self.nodes.len()];
+        for (start, end) in self.edges() {
+            edges[end].push(start);
+        }
+        let mut running_offset: usize = 0;
+        Self {
+            adjacency_offsets: edges
+                .iter()
+                .map(|parents: &Vec<usize>| {
+                    let old_offset = running_offset;
+                    running_offset += parents.len();
+                    old_offset
+                })
+                .collect(),
+            adjacency: edges.into_iter().flatten().collect(),
+            nodes: self.nodes.clone(),
+            node_positions: self.node_positions.clone(),
+        }
+    }
+
+    /// Marks every node that can be reached from one of the `starts`, including themselves.
+    pub fn reachable(&self, starts: impl IntoIterator<Item = usize>) -> Vec<bool> {
+        let mut seen = vec![false; self.nodes.len()];
+        let mut stack = Vec::new();
+        for start in starts {
+            if !std::mem::replace(&mut seen[start], true) {
+                stack.push(start);
+            }
+        }
+        while let Some(node_ix) = stack.pop() {
+            for child_ix in self.children(node_ix) {
+                if !std::mem::replace(&mut seen[*child_ix], true) {
+                    stack.push(*child_ix);
+                }
+            }
+        }
+        seen
+    }
+
+    /// A topological order found by depth first search, or the nodes of a cycle on failure.
+    pub fn topological_sort(
+        &self,
+    ) -> Result<impl Iterator<Item = &N> + Debug, impl Iterator<Item = &N> + Debug> {
+        #[derive(Debug, Clone, Copy)]
+        enum VisitState {
+            Unseen,
+            InProgress,
+            Visited,
+        }
+
+        #[derive(Debug)]
+        enum NodeIter<'a> {
+            AllNodes(Range<usize>),
+            Children {
+                node_ix: usize,
+                children: std::slice::Iter<'a, usize>,
+            },
+        }
+
+        impl Iterator for NodeIter<'_> {
+            type Item = usize;
+
+            fn next(&mut self) -> Option<Self::Item> {
+                match self {
+                    Self::AllNodes(range) => range.next(),
+                    Self::Children { children, .. } => children.next().copied(),
+                }
+            }
+        }
+
+        impl NodeIter<'_> {
+            fn node_ix(&self) -> Option<usize> {
+                match self {
+                    Self::Children { node_ix, .. } => Some(*node_ix),
+                    _ => None,
+                }
+            }
+        }
+
+        let mut state = vec![VisitState::Unseen; self.nodes.len()];
+        let mut stack = vec![NodeIter::AllNodes(0..self.nodes.len())];
+        let mut reverse_order_indices: Vec<usize> = Vec::new();
+
+        while let Some(node_iter) = stack.last_mut() {
+            match node_iter.next() {
+                Some(child_ix) => match state[child_ix] {
+                    VisitState::Unseen => {
+                        state[child_ix] = VisitState::InProgress;
+                        stack.push(NodeIter::Children {
+                            node_ix: child_ix,
+                            children: self.children(child_ix).iter(),
+                        });
+                    }
+                    VisitState::InProgress => {
+                        return Err(stack
+                            .into_iter()
+                            .flat_map(|node_iter| node_iter.node_ix())
+                            .skip_while(move |ix| *ix != child_ix)
+                            .map(|i| &self.nodes[i]))
+                    }
+                    VisitState::Visited => {}
+                },
+                None => {
+                    if let Some(node_ix) = node_iter.node_ix() {
+                        state[node_ix] = VisitState::Visited;
+                        reverse_order_indices.push(node_ix);
+                    }
+                    stack.pop();
+                }
+            }
+        }
+        Ok(reverse_order_indices
+            .into_iter()
+            .rev()
+            .map(|ix| &self.nodes[ix]))
+    }
+
+    /// A topological order found by Kahn's algorithm, which repeatedly takes the node with the
+    /// smallest index among those without remaining parents. On failure, returns the nodes
+    /// that lie on or behind a cycle.
+    pub fn kahn_sort(&self) -> Result<Vec<&N>, Vec<&N>> {
+        let mut in_degrees = vec![0; self.nodes.len()];
+        for (_, end) in self.edges() {
+            in_degrees[end] += 1;
+        }
+        let mut ready: BinaryHeap<_> = (0..self.nodes.len())
+            .filter(|ix| in_degrees[*ix] == 0)
+            .map(Reverse)
+            .collect();
+        let mut order = Vec::with_capacity(self.nodes.len());
+        while let Some(Reverse(node_ix)) = ready.pop() {
+            order.push(&self.nodes[node_ix]);
+            for child_ix in self.children(node_ix) {
+                in_degrees[*child_ix] -= 1;
+                if in_degrees[*child_ix] == 0 {
+                    ready.push(Reverse(*child_ix));
+                }
+            }
+        }
+        if order.len() == self.nodes.len() {
+            Ok(order)
+        } else {
+            Err((0..self.nodes.len())
+                .filter(|ix| in_degrees[*ix] > 0)
+                .map(|ix| &self.nodes[ix])
+                .collect())
+        }
+    }
+
+    /// The strongly connected components by Tarjan's algorithm, as lists of node indices.
+    /// Components come in reverse topological order: no edge leads to a later component.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<usize>> {
+        const UNSEEN: usize = usize::MAX;
+        let mut index = vec![UNSEEN; self.nodes.len()];
+        let mut low_link = vec![0; self.nodes.len()];
+        let mut on_stack = vec![false; self.nodes.len()];
+        let mut stack = Vec::new();
+        let mut components = Vec::new();
+        let mut next_index = 0;
+
+        for root in 0..self.nodes.len() {
+            if index[root] != UNSEEN {
+                continue;
+            }
+            // Frames of the depth first search: a node and the position of its next child.
+            let mut frames = vec![(root, 0)];
+            index[root] = next_index;
+            low_link[root] = next_index;
+            next_index += 1;
+            stack.push(root);
+            on_stack[root] = true;
+
+            while let Some((node_ix, child_pos)) = frames.last_mut() {
+                let node_ix = *node_ix;
+                if let Some(child_ix) = self.children(node_ix).get(*child_pos).copied() {
+                    *child_pos += 1;
+                    if index[child_ix] == UNSEEN {
+                        index[child_ix] = next_index;
+                        low_link[child_ix] = next_index;
+                        next_index += 1;
+                        stack.push(child_ix);
+                        on_stack[child_ix] = true;
+                        frames.push((child_ix, 0));
+                    } else if on_stack[child_ix] {
+                        low_link[node_ix] = low_link[node_ix].min(index[child_ix]);
+                    }
+                    continue;
+                }
+                frames.pop();
+                if let Some((parent_ix, _)) = frames.last() {
+                    low_link[*parent_ix] = low_link[*parent_ix].min(low_link[node_ix]);
+                }
+                if low_link[node_ix] == index[node_ix] {
+                    let mut component = Vec::new();
+                    loop {
+                        let member = stack.pop().expect("node is on the stack");
+                        on_stack[member] = false;
+                        component.push(member);
+                        if member == node_ix {
+                            break;
+                        }
+                    }
+                    components.push(component);
+                }
+            }
+        }
+        components
+    }
+}
+
+impl<N> FromIterator<(N, N)> for DirectedGraph<N>
+where
+    N: Hash + Eq + Clone,
+{
+    fn from_iter<T: IntoIterator<Item = (N, N)>>(iter: T) -> Self {
+        let mut result = DirectedGraphBuilder::default();
+        result.extend(iter);
+        result.build()
+    }
+}
+
+/// An undirected simple graph with interned nodes. Adjacency is kept both as neighbour lists
+/// and as one bitset row per node, so common neighbourhoods are a single `&`.
+#[derive(Debug, Clone)]
+pub struct UndirectedGraph<N> {
+    nodes: Vec<N>,
+    node_positions: HashMap<N, usize>,
+    neighbors: Vec<Vec<usize>>,
+    adjacency: Vec<BitVec>,
+}
+
+impl<N> Default for UndirectedGraph<N> {
+    fn default() -> Self {
+        Self {
+            nodes: Default::default(),
+            node_positions: Default::default(),
+            neighbors: Default::default(),
+            adjacency: Default::default(),
+        }
+    }
+}
+
+impl<N> UndirectedGraph<N>
+where
+    N: Hash + Eq + Clone,
+{
+    /// Adds a node without edges, unless it is already present, and returns its index.
+    pub fn add_node(&mut self, node: N) -> usize {
+        if let Some(node_ix) = self.node_positions.get(&node) {
+            return *node_ix;
+        }
+        let node_ix = self.nodes.len();
+        self.nodes.push(node.clone());
+        self.node_positions.insert(node, node_ix);
+        self.neighbors.push(Vec::new());
+        for row in &mut self.adjacency {
+            row.push(false);
+        }
+        self.adjacency.push(BitVec::repeat(false, self.nodes.len()));
+        node_ix
+    }
+
+    /// Adds an edge between two nodes. Loops and repeated edges are ignored.
+    pub fn add_edge(&mut self, a: N, b: N) {
+        let a = self.add_node(a);
+        let b = self.add_node(b);
+        if a != b && !self.adjacency[a].replace(b, true) {
+            self.adjacency[b].set(a, true);
+            self.neighbors[a].push(b);
+            self.neighbors[b].push(a);
+        }
+    }
+}
+
+impl<N> UndirectedGraph<N>
+where
+    N: Hash + Eq,
+{
+    /// The index of `node`, if it is part of the graph.
+    pub fn index_of(&self, node: &N) -> Option<usize> {
+        self.node_positions.get(node).copied()
+    }
+}
+
+impl<N> UndirectedGraph<N> {
+    pub fn nodes(&self) -> &[N] {
+        &self.nodes
+    }
+
+    pub fn node(&self, node_ix: usize) -> &N {
+        &self.nodes[node_ix]
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn neighbors(&self, node_ix: usize) -> &[usize] {
+        &self.neighbors[node_ix]
+    }
+
+    pub fn has_edge(&self, a: usize, b: usize) -> bool {
+        self.adjacency[a][b]
+    }
+
+    /// Every triangle once, as node indices in increasing order.
+    pub fn triangles(&self) -> impl Iterator<Item = [usize; 3]> + '_ {
+        (0..self.nodes.len()).flat_map(move |a| {
+            self.neighbors[a]
+                .iter()
+                .filter(move |b| **b > a)
+                .flat_map(move |b| {
+                    let common = self.adjacency[a].clone() & &self.adjacency[*b];
+                    common
+                        .iter_ones()
+                        .filter(|c| c > b)
+                        .map(|c| [a, *b, c])
+                        .collect_vec()
+                })
+        })
+    }
+
+    /// The nodes in an order where every node has as few neighbours later in the order as
+    /// possible, found by repeatedly removing a node of minimum remaining degree.
+    pub fn degeneracy_order(&self) -> Vec<usize> {
+        let mut degrees = self.neighbors.iter().map(Vec::len).collect_vec();
+        let mut removed = vec![false; self.nodes.len()];
+        let mut order = Vec::with_capacity(self.nodes.len());
+        for _ in 0..self.nodes.len() {
+            let node_ix = (0..self.nodes.len())
+                .filter(|ix| !removed[*ix])
+                .min_by_key(|ix| degrees[*ix])
+                .expect("nodes remain");
+            removed[node_ix] = true;
+            order.push(node_ix);
+            for neighbor in &self.neighbors[node_ix] {
+                degrees[*neighbor] = degrees[*neighbor].saturating_sub(1);
+            }
+        }
+        order
+    }
+
+    /// All maximal cliques by Bron–Kerbosch with pivoting, started in degeneracy order.
+    pub fn maximal_cliques(&self) -> Vec<Vec<usize>> {
+        let mut cliques = Vec::new();
+        let mut later = BitVec::repeat(true, self.nodes.len());
+        for node_ix in self.degeneracy_order() {
+            later.set(node_ix, false);
+            let candidates = self.adjacency[node_ix].clone() & &later;
+            let excluded = self.adjacency[node_ix].clone() & !later.clone();
+            self.bron_kerbosch(&mut vec![node_ix], candidates, excluded, &mut cliques);
+        }
+        cliques
+    }
+
+    fn bron_kerbosch(
+        &self,
+        clique: &mut Vec<usize>,
+        mut candidates: BitVec,
+        mut excluded: BitVec,
+        cliques: &mut Vec<Vec<usize>>,
+    ) {
+        if candidates.not_any() {
+            if excluded.not_any() {
+                cliques.push(clique.clone());
+            }
+            return;
+        }
+        // Branching only on non-neighbours of the pivot still finds every maximal clique.
+        let pivot = candidates
+            .iter_ones()
+            .chain(excluded.iter_ones())
+            .max_by_key(|ix| (self.adjacency[*ix].clone() & &candidates).count_ones())
+            .expect("candidates are not empty");
+        let branches = (candidates.clone() & !self.adjacency[pivot].clone())
+            .iter_ones()
+            .collect_vec();
+        for node_ix in branches {
+            clique.push(node_ix);
+            self.bron_kerbosch(
+                clique,
+                candidates.clone() & &self.adjacency[node_ix],
+                excluded.clone() & &self.adjacency[node_ix],
+                cliques,
+            );
+            clique.pop();
+            candidates.set(node_ix, false);
+            excluded.set(node_ix, true);
+        }
+    }
+
+    /// A largest clique, empty for an empty graph.
+    pub fn maximum_clique(&self) -> Vec<usize> {
+        self.maximal_cliques()
+            .into_iter()
+            .max_by_key(Vec::len)
+            .unwrap_or_default()
+    }
+
+    /// The connected components as lists of node indices.
+    pub fn connected_components(&self) -> Vec<Vec<usize>> {
+        let mut seen = vec![false; self.nodes.len()];
+        let mut components = Vec::new();
+        for root in 0..self.nodes.len() {
+            if std::mem::replace(&mut seen[root], true) {
+                continue;
+            }
+            let mut component = vec![root];
+            let mut stack = vec![root];
+            while let Some(node_ix) = stack.pop() {
+                for neighbor in &self.neighbors[node_ix] {
+                    if !std::mem::replace(&mut seen[*neighbor], true) {
+                        component.push(*neighbor);
+                        stack.push(*neighbor);
+                    }
+                }
+            }
+            components.push(component);
+        }
+        components
+    }
+}
+
+impl<N> FromIterator<(N, N)> for UndirectedGraph<N>
+where
+    N: Hash + Eq + Clone,
+{
+    fn from_iter<T: IntoIterator<Item = (N, N)>>(iter: T) -> Self {
+        let mut result = Self::default();
+        for (a, b) in iter {
+            result.add_edge(a, b);
+        }
+        result
+    }
+}
+
+impl<N: Display> DirectedGraph<N> {
+    /// The graph in DOT format, with nodes named by their `Display` output.
+    pub fn to_dot(&self, name: &str) -> Dot {
+        let mut dot = Dot::digraph(name);
+        for node in &self.nodes {
+            dot.node(node, &[]);
+        }
+        for (start, end) in self.edges() {
+            dot.edge(&self.nodes[start], &self.nodes[end], &[]);
+        }
+        dot
+    }
+}
+
+impl<N: Display> UndirectedGraph<N> {
+    /// The graph in DOT format, with the `highlighted` nodes and the edges between them in red.
+    pub fn to_dot(&self, name: &str, highlighted: &[usize]) -> Dot {
+        let mut is_highlighted = vec![false; self.nodes.len()];
+        for node_ix in highlighted {
+            is_highlighted[*node_ix] = true;
+        }
+        let mut dot = Dot::graph(name);
+        for (node_ix, node) in self.nodes.iter().enumerate() {
+            if is_highlighted[node_ix] {
+                dot.node(node, &[("color", "red"), ("style", "filled")]);
+            } else {
+                dot.node(node, &[]);
+            }
+        }
+        for (a, neighbors) in self.neighbors.iter().enumerate() {
+            for b in neighbors.iter().filter(|b| **b > a) {
+                if is_highlighted[a] && is_highlighted[*b] {
+                    dot.edge(&self.nodes[a], &self.nodes[*b], &[("color", "red")]);
+                } else {
+                    dot.edge(&self.nodes[a], &self.nodes[*b], &[]);
+                }
+            }
+        }
+        dot
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// `1 -> 2 -> 3 -> 1` is a cycle that `4` feeds into, and `3 -> 5` leaves it.
+    fn cyclic() -> DirectedGraph<u32> {
+        [(4, 1), (1, 2), (2, 3), (3, 1), (3, 5)]
+            .into_iter()
+            .collect()
+    }
+
+    #[test]
+    fn orders() {
+        let graph: DirectedGraph<u32> = [(1, 2), (3, 2), (2, 4), (1, 4)].into_iter().collect();
+        let dfs = graph.topological_sort().unwrap().copied().collect_vec();
+        assert!(graph.is_sub_topological_order(&dfs));
+        let kahn = graph
+            .kahn_sort()
+            .unwrap()
+            .into_iter()
+            .copied()
+            .collect_vec();
+        assert_eq!(kahn, [1, 3, 2, 4]);
+        assert!(!graph.is_sub_topological_order(&[4, 1]));
+
+        let cyclic = cyclic();
+        let cycle = cyclic
+            .topological_sort()
+            .unwrap_err()
+            .copied()
+            .collect_vec();
+        assert_eq!(cycle.into_iter().sorted().collect_vec(), [1, 2, 3]);
+        let stuck = cyclic
+            .kahn_sort()
+            .unwrap_err()
+            .into_iter()
+            .copied()
+            .sorted();
+        assert_eq!(stuck.collect_vec(), [1, 2, 3, 5]);
+    }
+
+    #[test]
+    fn components_and_reachability() {
+        let graph = cyclic();
+        let components = graph
+            .strongly_connected_components()
+            .into_iter()
+            .map(|component| {
+                component
+                    .into_iter()
+                    .map(|ix| *graph.node(ix))
+                    .sorted()
+                    .collect_vec()
+            })
+            .collect_vec();
+        assert_eq!(components, [vec![5], vec![1, 2, 3], vec![4]]);
+        assert!(graph.is_reachable(&4, &5));
+        assert!(!graph.is_reachable(&5, &4));
+        let reverse = graph.reverse();
+        assert!(reverse.is_reachable(&5, &4));
+        assert_eq!(reverse.children(graph.index_of(&1).unwrap()).len(), 2);
+    }
+
+    #[test]
+    fn cliques() {
+        // Two triangles sharing the edge 2-3 plus the pendant edge 4-5 and an isolated pair.
+        let graph: UndirectedGraph<u32> = [
+            (1, 2),
+            (1, 3),
+            (2, 3),
+            (2, 4),
+            (3, 4),
+            (4, 5),
+            (6, 7),
+            (3, 2),
+        ]
+        .into_iter()
+        .collect();
+        let name = |clique: Vec<usize>| {
+            clique
+                .into_iter()
+                .map(|ix| *graph.node(ix))
+                .sorted()
+                .collect_vec()
+        };
+        let triangles = graph.triangles().map(|t| name(t.to_vec())).collect_vec();
+        assert_eq!(
+            triangles.into_iter().sorted().collect_vec(),
+            [[1, 2, 3], [2, 3, 4]]
+        );
+        let cliques = graph.maximal_cliques().into_iter().map(name).sorted();
+        assert_eq!(
+            cliques.collect_vec(),
+            [vec![1, 2, 3], vec![2, 3, 4], vec![4, 5], vec![6, 7]]
+        );
+        assert_eq!(graph.maximum_clique().len(), 3);
+        assert_eq!(graph.connected_components().len(), 2);
+        assert!(graph.has_edge(graph.index_of(&3).unwrap(), graph.index_of(&2).unwrap()));
+        let dot = graph.to_dot("g", &graph.maximum_clique()).to_string();
+        assert_eq!(dot.matches("--").count(), 7);
+        assert_eq!(dot.matches("[color=\"red\"]").count(), 3);
+    }
+}
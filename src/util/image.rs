@@ -0,0 +1,434 @@
+use std::{
+    fmt::Write as _,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use crossterm::style::Color;
+
+use super::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Rgb {
+    pub const BLACK: Self = Self::new(0, 0, 0);
+    pub const WHITE: Self = Self::new(255, 255, 255);
+    pub const GREY: Self = Self::new(128, 128, 128);
+    pub const RED: Self = Self::new(220, 40, 40);
+
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+
+    /// The colour as used in SVG and HTML, e.g. `#ff8000`.
+    pub fn hex(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+/// A colour that is stable for a given hash but differs visibly between neighbouring hashes.
+///
+/// This is the ramp the connected components of day 18 are plotted with.
+pub fn hash_color(hash: u32) -> Rgb {
+    Rgb {
+        r: ((37 * hash + 32) & 255) as u8,
+        g: ((149 * hash + 82) & 255) as u8,
+        b: ((67 * hash + 191) & 255) as u8,
+    }
+}
+
+impl From<Color> for Rgb {
+    /// Named colours are mapped to the usual xterm palette.
+    fn from(value: Color) -> Self {
+        match value {
+            Color::Rgb { r, g, b } => Self { r, g, b },
+            Color::Reset | Color::Black => Self::BLACK,
+            Color::DarkGrey => Self::new(128, 128, 128),
+            Color::Red => Self::new(255, 0, 0),
+            Color::DarkRed => Self::new(128, 0, 0),
+            Color::Green => Self::new(0, 255, 0),
+            Color::DarkGreen => Self::new(0, 128, 0),
+            Color::Yellow => Self::new(255, 255, 0),
+            Color::DarkYellow => Self::new(128, 128, 0),
+            Color::Blue => Self::new(0, 0, 255),
+            Color::DarkBlue => Self::new(0, 0, 128),
+            Color::Magenta => Self::new(255, 0, 255),
+            Color::DarkMagenta => Self::new(128, 0, 128),
+            Color::Cyan => Self::new(0, 255, 255),
+            Color::DarkCyan => Self::new(0, 128, 128),
+            Color::White => Self::WHITE,
+            Color::Grey => Self::new(192, 192, 192),
+            Color::AnsiValue(_) => Self::GREY,
+        }
+    }
+}
+
+impl From<Rgb> for Color {
+    fn from(value: Rgb) -> Self {
+        Color::Rgb {
+            r: value.r,
+            g: value.g,
+            b: value.b,
+        }
+    }
+}
+
+/// A raster image, written out as PPM or PNG.
+#[derive(Debug, Clone)]
+pub struct Raster {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Raster {
+    /// Paints a `width` by `height` grid of cells, each `cell_size` pixels wide.
+    pub fn from_cells(
+        width: usize,
+        height: usize,
+        cell_size: usize,
+        mut color: impl FnMut(GridPos) -> Rgb,
+    ) -> Self {
+        let mut pixels = vec![Rgb::default(); width * height * cell_size * cell_size];
+        for y in 0..height {
+            for x in 0..width {
+                let c = color(GridPos { x, y });
+                for row in y * cell_size..(y + 1) * cell_size {
+                    let offset = row * width * cell_size;
+                    pixels[offset + x * cell_size..offset + (x + 1) * cell_size].fill(c);
+                }
+            }
+        }
+        Self {
+            width: width * cell_size,
+            height: height * cell_size,
+            pixels,
+        }
+    }
+
+    pub fn from_grid<T>(
+        grid: &Grid<T>,
+        cell_size: usize,
+        mut color: impl FnMut(&T) -> Rgb,
+    ) -> Self {
+        let colors = grid
+            .iter_rows()
+            .map(|row| row.map(&mut color).collect_vec())
+            .collect_vec();
+        Self::from_cells(grid.length, grid.height, cell_size, |pos| {
+            colors[pos.y][pos.x]
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<Rgb> {
+        (x < self.width && y < self.height).then(|| self.pixels[x + y * self.width])
+    }
+
+    fn raw_bytes(&self) -> impl Iterator<Item = u8> + '_ {
+        self.pixels.iter().flat_map(|c| [c.r, c.g, c.b])
+    }
+
+    pub fn write_ppm(&self, mut writer: impl Write) -> io::Result<()> {
+        write!(writer, "P6\n{} {}\n255\n", self.width, self.height)?;
+        writer.write_all(&self.raw_bytes().collect_vec())
+    }
+
+    /// Writes an uncompressed PNG, i.e. the zlib stream only uses stored blocks.
+    pub fn write_png(&self, mut writer: impl Write) -> io::Result<()> {
+        let mut header = Vec::with_capacity(13);
+        header.extend((self.width as u32).to_be_bytes());
+        header.extend((self.height as u32).to_be_bytes());
+        // bit depth 8, colour type truecolour, default compression, filter and interlacing
+        header.extend([8, 2, 0, 0, 0]);
+
+        let scanlines = if self.width == 0 {
+            Vec::new()
+        } else {
+            self.pixels
+                .chunks(self.width)
+                .flat_map(|row| std::iter::once(0).chain(row.iter().flat_map(|c| [c.r, c.g, c.b])))
+                .collect_vec()
+        };
+
+        writer.write_all(b"\x89PNG\r\n\x1a\n")?;
+        write_png_chunk(&mut writer, b"IHDR", &header)?;
+        write_png_chunk(&mut writer, b"IDAT", &zlib_stored(&scanlines))?;
+        write_png_chunk(&mut writer, b"IEND", &[])
+    }
+
+    /// Saves the image, picking the format from the extension (`png` or `ppm`).
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let png = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("png") => true,
+            Some("ppm") => false,
+            _ => return Err(invalid_extension("expected a .png or .ppm file")),
+        };
+        let mut writer = BufWriter::new(File::create(path)?);
+        if png {
+            self.write_png(&mut writer)?;
+        } else {
+            self.write_ppm(&mut writer)?;
+        }
+        writer.flush()
+    }
+}
+
+fn invalid_extension(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+/// Where to save the image `file_name` if the `IMAGE` environment variable names an output
+/// directory, which is created when missing. Each day picks its own file name, so one run over
+/// all days keeps every image.
+pub fn image_path(file_name: &str) -> io::Result<Option<PathBuf>> {
+    let Some(directory) = std::env::var_os("IMAGE") else {
+        return Ok(None);
+    };
+    let directory = PathBuf::from(directory);
+    std::fs::create_dir_all(&directory)?;
+    Ok(Some(directory.join(file_name)))
+}
+
+fn write_png_chunk(mut writer: impl Write, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    writer.write_all(&(data.len() as u32).to_be_bytes())?;
+    writer.write_all(kind)?;
+    writer.write_all(data)?;
+    let crc = crc32(kind.iter().chain(data).copied());
+    writer.write_all(&crc.to_be_bytes())
+}
+
+fn crc32(bytes: impl Iterator<Item = u8>) -> u32 {
+    let table: Vec<u32> = (0..256)
+        .map(|n| {
+            (0..8).fold(n, |c, _| {
+                if c & 1 == 1 {
+                    0xedb88320 ^ (c >> 1)
+                } else {
+                    c >> 1
+                }
+            })
+        })
+        .collect();
+    !bytes.fold(!0, |crc, byte| {
+        table[((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK: usize = u16::MAX as usize;
+    let mut result = vec![0x78, 0x01];
+    let block_count = data.len().div_ceil(MAX_BLOCK).max(1);
+    for i in 0..block_count {
+        let block = &data[i * MAX_BLOCK..((i + 1) * MAX_BLOCK).min(data.len())];
+        let len = block.len() as u16;
+        result.push((i + 1 == block_count) as u8);
+        result.extend(len.to_le_bytes());
+        result.extend((!len).to_le_bytes());
+        result.extend(block);
+    }
+    let (a, b) = data.iter().fold((1u32, 0u32), |(a, b), byte| {
+        let a = (a + *byte as u32) % 65521;
+        (a, (b + a) % 65521)
+    });
+    result.extend(((b << 16) | a).to_be_bytes());
+    result
+}
+
+/// A vector image over a grid of square cells.
+///
+/// Cells are addressed by [`GridPos`], while polygons and segments run along the grid lines,
+/// so the corner `[x, y]` is the top left corner of the cell at `x`, `y`.
+#[derive(Debug, Clone)]
+pub struct Svg {
+    width: usize,
+    height: usize,
+    cell_size: usize,
+    elements: Vec<String>,
+}
+
+impl Svg {
+    pub fn new(width: usize, height: usize, cell_size: usize) -> Self {
+        Self {
+            width,
+            height,
+            cell_size,
+            elements: Vec::new(),
+        }
+    }
+
+    pub fn cell(&mut self, pos: &GridPos, color: Rgb) {
+        self.elements.push(format!(
+            r#"<rect x="{}" y="{}" width="{s}" height="{s}" fill="{}"/>"#,
+            pos.x * self.cell_size,
+            pos.y * self.cell_size,
+            color.hex(),
+            s = self.cell_size,
+        ));
+    }
+
+    pub fn cells(&mut self, mut color: impl FnMut(GridPos) -> Option<Rgb>) {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let pos = GridPos { x, y };
+                if let Some(c) = color(pos) {
+                    self.cell(&pos, c);
+                }
+            }
+        }
+    }
+
+    /// A line through the centres of the given cells.
+    pub fn path<'a>(
+        &mut self,
+        cells: impl IntoIterator<Item = &'a GridPos>,
+        color: Rgb,
+        stroke_width: f32,
+    ) {
+        let half = self.cell_size as f32 / 2.0;
+        let points = cells
+            .into_iter()
+            .map(|pos| {
+                format!(
+                    "{},{}",
+                    (pos.x * self.cell_size) as f32 + half,
+                    (pos.y * self.cell_size) as f32 + half
+                )
+            })
+            .join(" ");
+        self.elements.push(format!(
+            r#"<polyline points="{points}" fill="none" stroke="{}" stroke-width="{stroke_width}" stroke-linejoin="round"/>"#,
+            color.hex()
+        ));
+    }
+
+    pub fn polygon(
+        &mut self,
+        corners: impl IntoIterator<Item = [usize; 2]>,
+        fill: Option<Rgb>,
+        stroke: Rgb,
+    ) {
+        let points = corners
+            .into_iter()
+            .map(|[x, y]| format!("{},{}", x * self.cell_size, y * self.cell_size))
+            .join(" ");
+        self.elements.push(format!(
+            r#"<polygon points="{points}" fill="{}" stroke="{}"/>"#,
+            fill.map_or("none".to_string(), |c| c.hex()),
+            stroke.hex()
+        ));
+    }
+
+    pub fn segment(&mut self, from: [usize; 2], to: [usize; 2], color: Rgb, stroke_width: f32) {
+        self.elements.push(format!(
+            r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}" stroke-width="{stroke_width}" stroke-linecap="square"/>"#,
+            from[0] * self.cell_size,
+            from[1] * self.cell_size,
+            to[0] * self.cell_size,
+            to[1] * self.cell_size,
+            color.hex()
+        ));
+    }
+
+    pub fn to_svg_string(&self) -> String {
+        let mut result = String::new();
+        writeln!(
+            result,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+            w = self.width * self.cell_size,
+            h = self.height * self.cell_size,
+        )
+        .expect("write to string");
+        for element in &self.elements {
+            writeln!(result, "  {element}").expect("write to string");
+        }
+        result.push_str("</svg>\n");
+        result
+    }
+
+    pub fn write(&self, mut writer: impl Write) -> io::Result<()> {
+        writer.write_all(self.to_svg_string().as_bytes())
+    }
+
+    /// Saves the image, which has to go to a `.svg` file.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("svg") {
+            return Err(invalid_extension("expected a .svg file"));
+        }
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write(&mut writer)?;
+        writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ppm_scales_cells() {
+        let raster = Raster::from_cells(
+            2,
+            1,
+            2,
+            |pos| {
+                if pos.x == 0 {
+                    Rgb::BLACK
+                } else {
+                    Rgb::WHITE
+                }
+            },
+        );
+        let mut bytes = Vec::new();
+        raster.write_ppm(&mut bytes).unwrap();
+        let (header, body) = bytes.split_at(b"P6\n4 2\n255\n".len());
+        assert_eq!(header, b"P6\n4 2\n255\n");
+        assert_eq!(body.len(), 4 * 2 * 3);
+        assert_eq!(raster.get(1, 1), Some(Rgb::BLACK));
+        assert_eq!(raster.get(2, 0), Some(Rgb::WHITE));
+    }
+
+    #[test]
+    fn png_checksums() {
+        assert_eq!(crc32(b"IEND".iter().copied()), 0xae426082);
+        let stream = zlib_stored(b"Wikipedia");
+        assert_eq!(&stream[stream.len() - 4..], &0x11e60398u32.to_be_bytes());
+    }
+
+    #[test]
+    fn png_layout() {
+        let raster = Raster::from_cells(3, 2, 1, |_| Rgb::RED);
+        let mut bytes = Vec::new();
+        raster.write_png(&mut bytes).unwrap();
+        assert_eq!(&bytes[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(&bytes[12..16], b"IHDR");
+        assert_eq!(&bytes[16..24], &[0, 0, 0, 3, 0, 0, 0, 2]);
+        assert_eq!(&bytes[bytes.len() - 8..bytes.len() - 4], b"IEND");
+    }
+
+    #[test]
+    fn save_checks_extension() {
+        let path = std::env::temp_dir().join("aoc-image-test.txt");
+        let raster = Raster::from_cells(1, 1, 1, |_| Rgb::RED);
+        let error = raster.save(&path).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        let error = Svg::new(1, 1, 1).save(&path).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert!(!path.exists());
+    }
+}
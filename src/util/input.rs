@@ -0,0 +1,222 @@
+use std::borrow::Cow;
+
+use nom::{
+    character::complete::{char, i64, newline},
+    combinator::{eof, opt},
+    multi::many1_count,
+    sequence::{pair, separated_pair},
+    Parser,
+};
+use nom_supreme::{
+    error::ErrorTree, final_parser::final_parser, multi::collect_separated_terminated,
+    tag::complete::tag, ParserExt,
+};
+
+use super::prelude::*;
+
+/// Puzzle input in a canonical shape: `\n` line endings, no trailing whitespace on any line, no
+/// blank lines at either end, and a single final newline unless the input is empty.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Input<'a> {
+    text: Cow<'a, str>,
+}
+
+fn is_normalized(raw: &str) -> bool {
+    !raw.contains('\r')
+        && !raw.starts_with('\n')
+        && (raw.is_empty() || (raw.ends_with('\n') && !raw.ends_with("\n\n")))
+        && raw.lines().all(|line| line.trim_end() == line)
+}
+
+impl<'a> Input<'a> {
+    pub fn new(raw: &'a str) -> Self {
+        if is_normalized(raw) {
+            return Self {
+                text: Cow::Borrowed(raw),
+            };
+        }
+        let mut text = String::with_capacity(raw.len() + 1);
+        // `lines` already drops a `\r` before each `\n`; the trim also catches stray ones.
+        for line in raw.lines() {
+            text.push_str(line.trim_end());
+            text.push('\n');
+        }
+        let text = text.trim_matches('\n');
+        let text = if text.is_empty() {
+            String::new()
+        } else {
+            format!("{text}\n")
+        };
+        Self {
+            text: Cow::Owned(text),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    pub fn lines(&self) -> std::str::Lines<'_> {
+        self.text.lines()
+    }
+
+    /// The groups of lines separated by one or more blank lines.
+    pub fn blocks(&self) -> impl Iterator<Item = Input<'_>> {
+        self.text
+            .split("\n\n")
+            .map(|block| block.trim_matches('\n'))
+            .filter(|block| !block.is_empty())
+            .map(Input::new)
+    }
+
+    /// Parses one cell per character, see [`Grid::from_str`].
+    pub fn grid<T>(&self, cell: impl FnMut(char) -> Option<T>) -> Result<Grid<T>, GridError> {
+        Grid::from_str(&self.text, cell)
+    }
+
+    /// All signed integers on each line.
+    pub fn ints(&self) -> impl Iterator<Item = Vec<i64>> + '_ {
+        self.lines().map(|line| ints(line).collect())
+    }
+
+    /// Runs a parser that has to consume the whole input.
+    pub fn parse<'b, T>(
+        &'b self,
+        parser: impl Parser<&'b str, T, ErrorTree<&'b str>>,
+    ) -> ParseFinalResult<'b, T> {
+        final_parser(parser)(self.as_str())
+    }
+}
+
+/// The signed integers in a string, ignoring everything in between. A `-` only counts as a sign
+/// directly in front of a digit. Numbers that don't fit into an `i64` are skipped entirely.
+pub fn ints(text: &str) -> impl Iterator<Item = i64> + '_ {
+    let mut rest = text;
+    std::iter::from_fn(move || loop {
+        let start = rest.find(|c: char| c.is_ascii_digit() || c == '-')?;
+        rest = &rest[start..];
+        match i64::<_, ErrorTree<&str>>(rest) {
+            Ok((after, value)) => {
+                rest = after;
+                return Some(value);
+            }
+            Err(_) => {
+                // Either a `-` without digits, or a digit run that overflows.
+                let sign = usize::from(rest.starts_with('-'));
+                let digits = rest[sign..]
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(rest.len() - sign);
+                rest = &rest[sign + digits..];
+            }
+        }
+    })
+}
+
+/// One record per line, with an optional newline at the end.
+pub fn lines_of<'a, T>(
+    record: impl Parser<&'a str, T, ErrorTree<&'a str>>,
+) -> impl Parser<&'a str, Vec<T>, ErrorTree<&'a str>> {
+    collect_separated_terminated(record, newline, eof.opt_preceded_by(newline))
+}
+
+/// One record per block of lines, with blocks separated by one or more blank lines like in
+/// [`Input::blocks`].
+pub fn blocks_of<'a, T>(
+    record: impl Parser<&'a str, T, ErrorTree<&'a str>>,
+) -> impl Parser<&'a str, Vec<T>, ErrorTree<&'a str>> {
+    collect_separated_terminated(
+        record,
+        newline.precedes(many1_count(newline)),
+        eof.opt_preceded_by(newline),
+    )
+}
+
+/// Two signed integers with a separator in between, like `3,-4`.
+pub fn int_pair<'a>(
+    separator: &'static str,
+) -> impl Parser<&'a str, (i64, i64), ErrorTree<&'a str>> {
+    separated_pair(i64, tag(separator), i64)
+}
+
+/// A `key: value` record, returning the key as written.
+pub fn key_value<'a, T>(
+    key: impl Parser<&'a str, &'a str, ErrorTree<&'a str>>,
+    value: impl Parser<&'a str, T, ErrorTree<&'a str>>,
+) -> impl Parser<&'a str, (&'a str, T), ErrorTree<&'a str>> {
+    separated_pair(key, pair(char(':'), opt(char(' '))), value)
+}
+
+#[cfg(test)]
+mod test {
+    use nom::{
+        character::complete::{alphanumeric1, u32},
+        multi::separated_list1,
+    };
+
+    use super::*;
+
+    #[test]
+    fn normalization() {
+        let input = Input::new("\r\n12  34\r\n5 6\t\r\n\r\n");
+        assert_eq!(input.as_str(), "12  34\n5 6\n");
+        assert_eq!(input.lines().collect_vec(), ["12  34", "5 6"]);
+        let clean = "a\n\nb\n";
+        assert!(matches!(Input::new(clean).text, Cow::Borrowed(_)));
+        assert_eq!(Input::new("no newline").as_str(), "no newline\n");
+        assert_eq!(Input::new("\n \n").as_str(), "");
+    }
+
+    #[test]
+    fn blocks_and_grids() {
+        let input = Input::new("#.\r\n.#\r\n\r\n\r\nab\r\ncd\r\n");
+        let blocks = input.blocks().collect_vec();
+        assert_eq!(blocks.len(), 2);
+        let grid = blocks[0].grid(|c| Some(c == '#')).unwrap();
+        assert!(grid[GridPos { x: 1, y: 1 }]);
+        assert_eq!(blocks[1].lines().collect_vec(), ["ab", "cd"]);
+    }
+
+    #[test]
+    fn integers() {
+        assert_eq!(
+            ints("p=0,4 v=3,-3 x-1 --2 a7b").collect_vec(),
+            [0, 4, 3, -3, -1, -2, 7]
+        );
+        assert_eq!(
+            ints("1 99999999999999999999 -99999999999999999999x2 -9223372036854775808")
+                .collect_vec(),
+            [1, 2, i64::MIN]
+        );
+        let input = Input::new("1 2\n-3\n\n4\n");
+        assert_eq!(
+            input.ints().collect_vec(),
+            [vec![1, 2], vec![-3], vec![], vec![4]]
+        );
+    }
+
+    #[test]
+    fn records() {
+        let input = Input::new("1,2\r\n-3,4");
+        assert_eq!(
+            input.parse(lines_of(int_pair(","))).unwrap(),
+            [(1, 2), (-3, 4)]
+        );
+        let input = Input::new("a: 1 2\nb: 3\n\nc:4\n");
+        let record = key_value(alphanumeric1, separated_list1(char(' '), u32));
+        let blocks = input.parse(blocks_of(separated_list1(newline, record)));
+        assert_eq!(
+            blocks.unwrap(),
+            [
+                vec![("a", vec![1, 2]), ("b", vec![3])],
+                vec![("c", vec![4])]
+            ]
+        );
+        let input = Input::new("a: 1\n\n\nb: 2\n\nc: 3\n");
+        let blocks = input.parse(blocks_of(key_value(alphanumeric1, u32)));
+        assert_eq!(blocks.unwrap(), [("a", 1), ("b", 2), ("c", 3)]);
+    }
+}
@@ -0,0 +1,330 @@
+use std::fmt::Display;
+
+use itertools::Itertools;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MathError {
+    /// An intermediate or final value does not fit into the integer type.
+    Overflow,
+    /// The system of equations has no unique solution.
+    Singular,
+    /// A modulus or denominator is zero.
+    DivisionByZero,
+    /// The value has no inverse modulo the given modulus.
+    NotInvertible,
+    /// The congruences contradict each other.
+    Inconsistent,
+}
+
+impl Display for MathError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MathError::Overflow => write!(f, "arithmetic overflow"),
+            MathError::Singular => write!(f, "singular system"),
+            MathError::DivisionByZero => write!(f, "division by zero"),
+            MathError::NotInvertible => write!(f, "value is not invertible"),
+            MathError::Inconsistent => write!(f, "inconsistent congruences"),
+        }
+    }
+}
+
+impl std::error::Error for MathError {}
+
+fn narrow(value: i128) -> Result<i64, MathError> {
+    i64::try_from(value).map_err(|_| MathError::Overflow)
+}
+
+/// The greatest common divisor, using Stein's binary algorithm. `gcd(0, 0)` is zero.
+pub fn gcd(mut x: u64, mut y: u64) -> u64 {
+    if x == 0 || y == 0 {
+        return x | y;
+    }
+    let bits = (x | y).trailing_zeros();
+    x >>= x.trailing_zeros();
+    y >>= y.trailing_zeros();
+
+    if x > y {
+        std::mem::swap(&mut y, &mut x);
+    }
+    while x > 0 {
+        x >>= x.trailing_zeros();
+        let z = x;
+        x = y.abs_diff(x);
+        y = z;
+    }
+    y << bits
+}
+
+/// The least common multiple. `lcm(0, x)` is zero.
+pub fn lcm(x: u64, y: u64) -> Result<u64, MathError> {
+    if x == 0 || y == 0 {
+        return Ok(0);
+    }
+    (x / gcd(x, y)).checked_mul(y).ok_or(MathError::Overflow)
+}
+
+/// Extended Euclid: `(g, s, t)` with `g = gcd(a, b) >= 0` and `a * s + b * t = g`. Fails with
+/// [`MathError::Overflow`] if `g` doesn't fit, which only happens for `i64::MIN` with zero or
+/// with itself.
+pub fn extended_gcd(a: i64, b: i64) -> Result<(i64, i64, i64), MathError> {
+    let (g, s, t) = extended_gcd_wide(a as i128, b as i128);
+    Ok((narrow(g)?, narrow(s)?, narrow(t)?))
+}
+
+fn extended_gcd_wide(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_s, mut s) = (1i128, 0i128);
+    let (mut old_t, mut t) = (0i128, 1i128);
+    while r != 0 {
+        let quotient = old_r.div_euclid(r);
+        (old_r, r) = (r, old_r - quotient * r);
+        (old_s, s) = (s, old_s - quotient * s);
+        (old_t, t) = (t, old_t - quotient * t);
+    }
+    if old_r < 0 {
+        (old_r, old_s, old_t) = (-old_r, -old_s, -old_t);
+    }
+    (old_r, old_s, old_t)
+}
+
+/// The inverse of `a` modulo `|m|`, in `[0, |m|)`.
+pub fn mod_inverse(a: i64, m: i64) -> Result<i64, MathError> {
+    if m == 0 {
+        return Err(MathError::DivisionByZero);
+    }
+    // `|i64::MIN|` only fits when widened, but the inverse is below it and fits again.
+    let m = m.unsigned_abs() as i128;
+    let (g, s, _) = extended_gcd_wide((a as i128).rem_euclid(m), m);
+    if g != 1 {
+        return Err(MathError::NotInvertible);
+    }
+    narrow(s.rem_euclid(m))
+}
+
+/// Solves the system `x = residue (mod modulus)` for all pairs, returning `(x, lcm)` with
+/// `x` in `[0, lcm)`. The moduli need not be coprime, as long as the congruences agree.
+pub fn crt(congruences: impl IntoIterator<Item = (i64, i64)>) -> Result<(i64, i64), MathError> {
+    let (mut x, mut m) = (0i128, 1i128);
+    for (residue, modulus) in congruences {
+        if modulus == 0 {
+            return Err(MathError::DivisionByZero);
+        }
+        let (residue, modulus) = (residue as i128, (modulus as i128).abs());
+        // x + m * k = residue (mod modulus)  <=>  m * k = residue - x (mod modulus)
+        let (g, s, _) = extended_gcd_wide(m, modulus);
+        let difference = residue - x;
+        if difference % g != 0 {
+            return Err(MathError::Inconsistent);
+        }
+        let step = modulus / g;
+        let k = (difference / g % step * s).rem_euclid(step);
+        x += m * k;
+        m = m.checked_mul(step).ok_or(MathError::Overflow)?;
+        x = x.rem_euclid(m);
+        narrow(m)?;
+    }
+    Ok((narrow(x)?, narrow(m)?))
+}
+
+/// An exact fraction in lowest terms with a positive denominator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rational {
+    numerator: i64,
+    denominator: i64,
+}
+
+impl Rational {
+    pub fn new(numerator: i64, denominator: i64) -> Result<Self, MathError> {
+        if denominator == 0 {
+            return Err(MathError::DivisionByZero);
+        }
+        // Widened, since negating `i64::MIN` overflows.
+        let (numerator, denominator) = (numerator as i128, denominator as i128);
+        let divisor = gcd_wide(numerator, denominator) * denominator.signum();
+        Ok(Self {
+            numerator: narrow(numerator / divisor)?,
+            denominator: narrow(denominator / divisor)?,
+        })
+    }
+
+    pub fn integer(value: i64) -> Self {
+        Self {
+            numerator: value,
+            denominator: 1,
+        }
+    }
+
+    pub fn numerator(&self) -> i64 {
+        self.numerator
+    }
+
+    pub fn denominator(&self) -> i64 {
+        self.denominator
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.denominator == 1
+    }
+
+    pub fn to_integer(&self) -> Option<i64> {
+        self.is_integer().then_some(self.numerator)
+    }
+}
+
+impl Display for Rational {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_integer() {
+            write!(f, "{}", self.numerator)
+        } else {
+            write!(f, "{}/{}", self.numerator, self.denominator)
+        }
+    }
+}
+
+fn gcd_wide(x: i128, y: i128) -> i128 {
+    let (mut x, mut y) = (x.unsigned_abs(), y.unsigned_abs());
+    while y != 0 {
+        (x, y) = (y, x % y);
+    }
+    x as i128
+}
+
+/// The determinant of a square matrix, by fraction-free Gaussian elimination (Bareiss).
+pub fn determinant(matrix: &[Vec<i64>]) -> Result<i64, MathError> {
+    let n = matrix.len();
+    assert!(
+        matrix.iter().all(|row| row.len() == n),
+        "the matrix must be square"
+    );
+    let mut a = matrix
+        .iter()
+        .map(|row| row.iter().map(|x| *x as i128).collect_vec())
+        .collect_vec();
+    let mut sign = 1;
+    let mut previous_pivot = 1i128;
+    for k in 0..n {
+        let Some(pivot_row) = (k..n).find(|row| a[*row][k] != 0) else {
+            return Ok(0);
+        };
+        if pivot_row != k {
+            a.swap(pivot_row, k);
+            sign = -sign;
+        }
+        for i in k + 1..n {
+            for j in k + 1..n {
+                // Every entry is a minor of the input, so the division is exact.
+                let value = a[i][j]
+                    .checked_mul(a[k][k])
+                    .zip(a[i][k].checked_mul(a[k][j]))
+                    .and_then(|(x, y)| x.checked_sub(y))
+                    .ok_or(MathError::Overflow)?;
+                a[i][j] = value / previous_pivot;
+            }
+        }
+        previous_pivot = a[k][k];
+    }
+    narrow(sign * if n == 0 { 1 } else { a[n - 1][n - 1] })
+}
+
+/// The unique solution of `matrix * x = rhs`, by Cramer's rule. Meant for small systems.
+pub fn solve_rational(matrix: &[Vec<i64>], rhs: &[i64]) -> Result<Vec<Rational>, MathError> {
+    assert_eq!(matrix.len(), rhs.len(), "one right-hand side per row");
+    let det = determinant(matrix)?;
+    if det == 0 {
+        return Err(MathError::Singular);
+    }
+    (0..rhs.len())
+        .map(|column| {
+            let replaced = matrix
+                .iter()
+                .zip(rhs)
+                .map(|(row, b)| {
+                    let mut row = row.clone();
+                    row[column] = *b;
+                    row
+                })
+                .collect_vec();
+            Rational::new(determinant(&replaced)?, det)
+        })
+        .collect()
+}
+
+/// The unique solution of `matrix * x = rhs` if it is integral, `None` if it is fractional.
+pub fn solve_integer(matrix: &[Vec<i64>], rhs: &[i64]) -> Result<Option<Vec<i64>>, MathError> {
+    Ok(solve_rational(matrix, rhs)?
+        .iter()
+        .map(Rational::to_integer)
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn divisibility() {
+        assert_eq!(gcd(12, 18), 6);
+        assert_eq!(gcd(0, 7), 7);
+        assert_eq!(gcd(0, 0), 0);
+        assert_eq!(lcm(4, 6), Ok(12));
+        assert_eq!(lcm(u64::MAX, 2), Err(MathError::Overflow));
+        let (g, s, t) = extended_gcd(240, -46).unwrap();
+        assert_eq!(g, 2);
+        assert_eq!(240 * s - 46 * t, 2);
+        assert_eq!(extended_gcd(i64::MIN, 0), Err(MathError::Overflow));
+        assert_eq!(extended_gcd(i64::MIN, i64::MIN), Err(MathError::Overflow));
+        assert_eq!(extended_gcd(i64::MIN, 6).map(|(g, _, _)| g), Ok(2));
+        assert_eq!(mod_inverse(3, 11), Ok(4));
+        assert_eq!(mod_inverse(-3, 11), Ok(7));
+        assert_eq!(mod_inverse(4, 6), Err(MathError::NotInvertible));
+        assert_eq!(mod_inverse(3, -11), Ok(4));
+        let inverse = mod_inverse(3, i64::MIN).unwrap();
+        assert_eq!((3 * inverse as i128).rem_euclid(1 << 63), 1);
+        assert_eq!(mod_inverse(2, i64::MIN), Err(MathError::NotInvertible));
+        assert_eq!(crt([(1, i64::MIN)]), Err(MathError::Overflow));
+    }
+
+    #[test]
+    fn chinese_remainder() {
+        assert_eq!(crt([(2, 3), (3, 5), (2, 7)]), Ok((23, 105)));
+        assert_eq!(crt([(2, 4), (4, 6)]), Ok((10, 12)));
+        assert_eq!(crt([(1, 4), (2, 6)]), Err(MathError::Inconsistent));
+        assert_eq!(crt([]), Ok((0, 1)));
+        let big = (1 << 40) + 15;
+        assert_eq!(
+            crt([(1, big), (2, big + 2), (3, big + 4)]),
+            Err(MathError::Overflow)
+        );
+    }
+
+    #[test]
+    fn linear_systems() {
+        let matrix = vec![vec![94, 22], vec![34, 67]];
+        assert_eq!(determinant(&matrix), Ok(5550));
+        assert_eq!(
+            solve_integer(&matrix, &[8400, 5400]),
+            Ok(Some(vec![80, 40]))
+        );
+        assert_eq!(solve_integer(&matrix, &[8401, 5400]), Ok(None));
+        assert_eq!(
+            solve_rational(&[vec![2, 0], vec![0, 4]], &[1, 2]),
+            Ok(vec![
+                Rational::new(1, 2).unwrap(),
+                Rational::new(1, 2).unwrap()
+            ])
+        );
+        assert_eq!(
+            solve_rational(&[vec![1, 2], vec![2, 4]], &[1, 2]),
+            Err(MathError::Singular)
+        );
+        let three = vec![vec![2, 1, -1], vec![-3, -1, 2], vec![-2, 1, 2]];
+        assert_eq!(determinant(&three), Ok(-1));
+        assert_eq!(
+            solve_integer(&three, &[8, -11, -3]),
+            Ok(Some(vec![2, 3, -1]))
+        );
+        let huge = vec![vec![i64::MAX, 1], vec![1, i64::MAX]];
+        assert_eq!(determinant(&huge), Err(MathError::Overflow));
+        assert_eq!(Rational::new(4, -6).unwrap().to_string(), "-2/3");
+    }
+}
@@ -1,2 +1,17 @@
+pub mod bitgrid;
+pub mod direction;
+pub mod dot;
+pub mod dsu;
+pub mod graph;
+pub mod image;
 pub mod infra;
+pub mod input;
+pub mod math;
+pub mod pattern;
 pub mod prelude;
+pub mod regions;
+pub mod search;
+pub mod sparse;
+pub mod torus;
+pub mod transform;
+pub mod vec2;
@@ -0,0 +1,272 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    hash::Hash,
+};
+
+use super::{prelude::*, transform::Transform};
+
+/// An occurrence of a word, read from `start` in the given heading.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WordMatch {
+    /// The index of the word in the list passed to [`WordSearch::new`].
+    pub word: usize,
+    pub start: GridPos,
+    pub heading: Heading,
+}
+
+/// An Aho–Corasick automaton that finds any number of words in a single pass over each line.
+#[derive(Debug, Clone)]
+pub struct WordSearch<T> {
+    transitions: Vec<HashMap<T, usize>>,
+    fail: Vec<usize>,
+    /// The words ending at each state, including those reached through fail links.
+    outputs: Vec<Vec<usize>>,
+    lengths: Vec<usize>,
+}
+
+impl WordSearch<char> {
+    pub fn from_words<'a>(words: impl IntoIterator<Item = &'a str>) -> Self {
+        Self::new(words.into_iter().map(str::chars))
+    }
+}
+
+impl<T: Hash + Eq + Clone> WordSearch<T> {
+    pub fn new<W: IntoIterator<Item = T>>(words: impl IntoIterator<Item = W>) -> Self {
+        let mut search = Self {
+            transitions: vec![HashMap::new()],
+            fail: vec![0],
+            outputs: vec![Vec::new()],
+            lengths: Vec::new(),
+        };
+        for (index, word) in words.into_iter().enumerate() {
+            let mut state = 0;
+            let mut length = 0;
+            for t in word {
+                length += 1;
+                state = match search.transitions[state].get(&t) {
+                    Some(next) => *next,
+                    None => {
+                        search.transitions.push(HashMap::new());
+                        search.fail.push(0);
+                        search.outputs.push(Vec::new());
+                        let next = search.transitions.len() - 1;
+                        search.transitions[state].insert(t, next);
+                        next
+                    }
+                };
+            }
+            assert!(length > 0, "empty words cannot be searched");
+            search.outputs[state].push(index);
+            search.lengths.push(length);
+        }
+
+        // Breadth first, so the fail link of every shorter suffix is known already.
+        let mut queue: VecDeque<_> = search.transitions[0].values().copied().collect();
+        while let Some(state) = queue.pop_front() {
+            let children = search.transitions[state]
+                .iter()
+                .map(|(t, child)| (t.clone(), *child))
+                .collect_vec();
+            for (t, child) in children {
+                let mut fallback = search.fail[state];
+                let fail = loop {
+                    if let Some(next) = search.transitions[fallback].get(&t) {
+                        break *next;
+                    }
+                    if fallback == 0 {
+                        break 0;
+                    }
+                    fallback = search.fail[fallback];
+                };
+                search.fail[child] = fail;
+                let inherited = search.outputs[fail].clone();
+                search.outputs[child].extend(inherited);
+                queue.push_back(child);
+            }
+        }
+        search
+    }
+
+    fn advance(&self, mut state: usize, t: &T) -> usize {
+        loop {
+            if let Some(next) = self.transitions[state].get(t) {
+                return *next;
+            }
+            if state == 0 {
+                return 0;
+            }
+            state = self.fail[state];
+        }
+    }
+
+    /// All occurrences of the words as `(word, start index)` in a sequence.
+    pub fn find_in<'a>(
+        &'a self,
+        sequence: impl IntoIterator<Item = &'a T> + 'a,
+    ) -> impl Iterator<Item = (usize, usize)> + 'a
+    where
+        T: 'a,
+    {
+        sequence
+            .into_iter()
+            .enumerate()
+            .scan(0, |state, (i, t)| {
+                *state = self.advance(*state, t);
+                Some((i, *state))
+            })
+            .flat_map(move |(i, state)| {
+                self.outputs[state]
+                    .iter()
+                    .map(move |word| (*word, i + 1 - self.lengths[*word]))
+            })
+    }
+
+    /// All occurrences of the words along straight lines in any of the eight headings.
+    ///
+    /// Palindromes are found once per reading direction, and single-cell words once per heading.
+    pub fn find_all<'a>(&'a self, grid: &'a Grid<T>) -> impl Iterator<Item = WordMatch> + 'a {
+        Heading::ALL.into_iter().flat_map(move |heading| {
+            grid.lines(heading).flat_map(move |line| {
+                let positions = line.clone().map(|(pos, _)| pos).collect_vec();
+                self.find_in(line.map(|(_, t)| t))
+                    .map(move |(word, start)| WordMatch {
+                        word,
+                        start: positions[start],
+                        heading,
+                    })
+                    .collect_vec()
+            })
+        })
+    }
+}
+
+/// An occurrence of a template with its top left corner at `pos`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TemplateMatch {
+    pub pos: GridPos,
+    /// The transformation of the original template that matched.
+    pub transform: Transform,
+}
+
+/// A rectangular pattern whose `None` cells match anything, optionally in several orientations.
+#[derive(Debug, Clone)]
+pub struct Template<T> {
+    variants: Vec<(Transform, Grid<Option<T>>)>,
+}
+
+impl Template<char> {
+    /// Parses a template where `wildcard` matches any cell.
+    pub fn parse(input: &str, wildcard: char) -> Result<Self, GridError> {
+        let cells = Grid::from_str(input, |c| Some((c != wildcard).then_some(c)))?;
+        Ok(Self::new(cells))
+    }
+}
+
+impl<T: PartialEq + Clone> Template<T> {
+    pub fn new(cells: Grid<Option<T>>) -> Self {
+        Self {
+            variants: vec![(Transform::IDENTITY, cells)],
+        }
+    }
+
+    fn with_transforms(mut self, transforms: &[Transform]) -> Self {
+        let (_, cells) = self.variants.swap_remove(0);
+        self.variants.clear();
+        for transform in transforms {
+            let variant = cells.view(*transform).to_grid();
+            if !self.variants.iter().any(|(_, other)| *other == variant) {
+                self.variants.push((*transform, variant));
+            }
+        }
+        self
+    }
+
+    /// Also matches the template turned by 90, 180 and 270 degrees.
+    pub fn with_rotations(self) -> Self {
+        self.with_transforms(&Transform::ROTATIONS)
+    }
+
+    /// Also matches all rotations and reflections of the template.
+    pub fn with_symmetries(self) -> Self {
+        self.with_transforms(&Transform::ALL)
+    }
+
+    /// The distinct orientations that are searched for.
+    pub fn variants(&self) -> impl Iterator<Item = &Grid<Option<T>>> + '_ {
+        self.variants.iter().map(|(_, cells)| cells)
+    }
+
+    fn matches_at(cells: &Grid<Option<T>>, grid: &Grid<T>, pos: GridPos) -> bool {
+        cells.enumerate().all(|(offset, cell)| {
+            let Some(expected) = cell else {
+                return true;
+            };
+            grid.get(&GridPos {
+                x: pos.x + offset.x,
+                y: pos.y + offset.y,
+            }) == Some(expected)
+        })
+    }
+
+    /// All occurrences in the grid, one per matching orientation.
+    pub fn find_all<'a>(&'a self, grid: &'a Grid<T>) -> impl Iterator<Item = TemplateMatch> + 'a {
+        self.variants.iter().flat_map(move |(transform, cells)| {
+            let xs = 0..(grid.length + 1).saturating_sub(cells.length);
+            let ys = 0..(grid.height + 1).saturating_sub(cells.height);
+            ys.cartesian_product(xs)
+                .map(|(y, x)| GridPos { x, y })
+                .filter(move |pos| Self::matches_at(cells, grid, *pos))
+                .map(move |pos| TemplateMatch {
+                    pos,
+                    transform: *transform,
+                })
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn overlapping_words() {
+        let search = WordSearch::from_words(["he", "she", "his", "hers"]);
+        let found = search
+            .find_in(&"ushers".chars().collect_vec())
+            .collect_vec();
+        assert_eq!(found, [(1, 1), (0, 2), (3, 2)]);
+    }
+
+    #[test]
+    fn words_in_grid() {
+        let grid = Grid::from_str("abc\nxbx\ncbc\n", Some).unwrap();
+        let search = WordSearch::from_words(["abc", "cb"]);
+        let found = search.find_all(&grid).collect_vec();
+        assert_eq!(found.iter().filter(|m| m.word == 0).count(), 2);
+        assert!(found.contains(&WordMatch {
+            word: 0,
+            start: GridPos { x: 0, y: 0 },
+            heading: Heading::SouthEast,
+        }));
+        assert_eq!(found.iter().filter(|m| m.word == 1).count(), 6);
+    }
+
+    #[test]
+    fn template_orientations() {
+        let template = Template::parse("M.S\n.A.\nM.S\n", '.').unwrap();
+        assert_eq!(template.clone().with_rotations().variants().count(), 4);
+        assert_eq!(template.with_symmetries().variants().count(), 4);
+
+        let corner = Template::parse("ab\nc.\n", '.').unwrap().with_symmetries();
+        assert_eq!(corner.variants().count(), 8);
+        let grid = Grid::from_str("xab\nxcy\n", Some).unwrap();
+        let found = corner.find_all(&grid).collect_vec();
+        assert_eq!(
+            found,
+            [TemplateMatch {
+                pos: GridPos { x: 1, y: 0 },
+                transform: Transform::IDENTITY
+            }]
+        );
+    }
+}
@@ -1,5 +1,12 @@
+use std::{
+    fmt::Display,
+    ops::{Index, IndexMut},
+};
+
+pub use super::direction::{Direction, Heading};
+pub use super::input::Input;
 pub use itertools::Itertools;
-use nom::{character::complete::line_ending, IResult, Parser};
+use nom::{character::complete::line_ending, error::FromExternalError, IResult, Parser};
 use nom_supreme::{error::ErrorTree, final_parser::Location};
 
 pub fn ascii_code(c: char) -> i64 {
@@ -9,7 +16,7 @@ pub fn ascii_code(c: char) -> i64 {
 pub const LOWER_A_ASCII: i64 = 97;
 pub const UPPER_A_ASCII: i64 = 65;
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, PartialOrd, Ord)]
 pub struct GridPos {
     pub x: usize,
     pub y: usize,
@@ -22,18 +29,9 @@ impl GridPos {
     }
 
     pub fn neighbors(&self, max_x: usize, max_y: usize) -> impl Iterator<Item = GridPos> + '_ {
-        let x = self.x as isize;
-        let y = self.y as isize;
-        [(-1, 0), (1, 0), (0, -1), (0, 1)]
+        Direction::ALL
             .into_iter()
-            .map(move |(dx, dy)| (x + dx, y + dy))
-            .filter_map(move |(x2, y2)| {
-                (((0..(max_x as isize)).contains(&x2)) && (0..(max_y as isize)).contains(&y2))
-                    .then_some(Self {
-                        x: x2 as usize,
-                        y: y2 as usize,
-                    })
-            })
+            .filter_map(move |direction| self.step_within(direction, max_x, max_y))
     }
 }
 
@@ -46,7 +44,37 @@ impl From<(usize, usize)> for GridPos {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GridError {
+    UnexpectedChar {
+        c: char,
+        pos: GridPos,
+    },
+    RaggedRow {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl Display for GridError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GridError::UnexpectedChar { c, pos } => {
+                write!(f, "unexpected character {c:?} at ({}, {})", pos.x, pos.y)
+            }
+            GridError::RaggedRow {
+                row,
+                expected,
+                found,
+            } => write!(f, "row {row} has {found} cells, expected {expected}"),
+        }
+    }
+}
+
+impl std::error::Error for GridError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Grid<T> {
     inner: Vec<T>,
     pub length: usize,
@@ -54,6 +82,93 @@ pub struct Grid<T> {
 }
 
 impl<T> Grid<T> {
+    pub fn new(length: usize, height: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            inner: vec![value; length * height],
+            length,
+            height,
+        }
+    }
+
+    /// Parses one cell per character, where `cell` returns `None` for unexpected characters.
+    pub fn from_str(
+        input: &str,
+        mut cell: impl FnMut(char) -> Option<T>,
+    ) -> Result<Self, GridError> {
+        let mut inner = Vec::new();
+        let mut length = 0;
+        let mut height = 0;
+        for (y, line) in input.lines().enumerate() {
+            let row_start = inner.len();
+            for (x, c) in line.chars().enumerate() {
+                inner.push(cell(c).ok_or(GridError::UnexpectedChar {
+                    c,
+                    pos: GridPos { x, y },
+                })?);
+            }
+            let found = inner.len() - row_start;
+            if y == 0 {
+                length = found;
+            } else if found != length {
+                return Err(GridError::RaggedRow {
+                    row: y,
+                    expected: length,
+                    found,
+                });
+            }
+            height += 1;
+        }
+        Ok(Self {
+            inner,
+            length,
+            height,
+        })
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            inner: self.inner.iter().map(f).collect(),
+            length: self.length,
+            height: self.height,
+        }
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.inner.iter()
+    }
+
+    /// All cells in reading order together with their positions.
+    pub fn enumerate(&self) -> impl Iterator<Item = (GridPos, &T)> + '_ {
+        let length = self.length;
+        self.inner.iter().enumerate().map(move |(i, t)| {
+            (
+                GridPos {
+                    x: i % length,
+                    y: i / length,
+                },
+                t,
+            )
+        })
+    }
+
+    pub fn find(&self, value: &T) -> Option<GridPos>
+    where
+        T: PartialEq,
+    {
+        self.find_all(value).next()
+    }
+
+    pub fn find_all<'a>(&'a self, value: &'a T) -> impl Iterator<Item = GridPos> + 'a
+    where
+        T: PartialEq,
+    {
+        self.enumerate()
+            .filter_map(move |(pos, t)| (t == value).then_some(pos))
+    }
+
     pub fn contains(&self, pos: &GridPos) -> bool {
         (0..self.length).contains(&pos.x) && (0..self.height).contains(&pos.y)
     }
@@ -72,12 +187,14 @@ impl<T> Grid<T> {
         }
     }
 
-    pub fn iter_rows(&self) -> impl Iterator<Item = std::slice::Iter<T>> {
-        self.inner.chunks(self.length).map(|s| s.iter())
+    pub fn iter_rows(&self) -> impl Iterator<Item = std::slice::Iter<'_, T>> {
+        self.inner.chunks(self.length.max(1)).map(|s| s.iter())
     }
 
-    pub fn iter_rows_mut(&mut self) -> impl Iterator<Item = std::slice::IterMut<T>> {
-        self.inner.chunks_mut(self.length).map(|s| s.iter_mut())
+    pub fn iter_rows_mut(&mut self) -> impl Iterator<Item = std::slice::IterMut<'_, T>> {
+        self.inner
+            .chunks_mut(self.length.max(1))
+            .map(|s| s.iter_mut())
     }
 
     pub fn neighbors<'a, 'b: 'a>(
@@ -90,50 +207,132 @@ impl<T> Grid<T> {
         })
     }
 
+    /// Parses rows separated by line endings, stopping before the first row whose length
+    /// differs from the first one. The remaining input starts at that row's line ending.
     pub fn parse<'a, F: Parser<&'a str, Vec<T>, ErrorTree<&'a str>>>(
+        line_parser: F,
+    ) -> impl Parser<&'a str, Self, ErrorTree<&'a str>> {
+        Self::parse_rows(line_parser, false)
+    }
+
+    /// Like [`Grid::parse`], but a nonempty row of the wrong length fails with a
+    /// [`GridError::RaggedRow`] instead of ending the grid.
+    pub fn parse_strict<'a, F: Parser<&'a str, Vec<T>, ErrorTree<&'a str>>>(
+        line_parser: F,
+    ) -> impl Parser<&'a str, Self, ErrorTree<&'a str>> {
+        Self::parse_rows(line_parser, true)
+    }
+
+    fn parse_rows<'a, F: Parser<&'a str, Vec<T>, ErrorTree<&'a str>>>(
         mut line_parser: F,
+        strict: bool,
     ) -> impl Parser<&'a str, Self, ErrorTree<&'a str>> {
-        move |input| match line_parser.parse(input) {
-            IResult::Ok((input, first_line)) => {
-                let length = first_line.len();
-                let mut acc = first_line;
-                let mut height = 1;
-                let mut rest = input;
-                loop {
-                    match line_ending::<_, nom::error::Error<&'a str>>(rest) {
-                        IResult::Ok((new_rest, _)) => rest = new_rest,
-                        Err(_) => {
-                            return IResult::Ok((
-                                input,
-                                Self {
-                                    inner: acc,
-                                    length,
-                                    height,
-                                },
-                            ))
-                        }
-                    }
-                    match line_parser.parse(rest) {
-                        IResult::Ok((new_rest, mut row)) if row.len() == length => {
-                            acc.append(&mut row);
-                            height += 1;
-                            rest = new_rest;
-                        }
-                        _ => {
-                            return IResult::Ok((
-                                input,
-                                Self {
-                                    inner: acc,
-                                    length,
-                                    height,
-                                },
-                            ))
-                        }
+        move |input| {
+            let (mut rest, mut inner) = line_parser.parse(input)?;
+            let length = inner.len();
+            let mut height = 1;
+            loop {
+                let Ok((row_start, _)) = line_ending::<_, ErrorTree<&'a str>>(rest) else {
+                    break;
+                };
+                let Ok((row_end, mut row)) = line_parser.parse(row_start) else {
+                    break;
+                };
+                if row.len() != length {
+                    if strict && !row.is_empty() {
+                        return Err(nom::Err::Failure(ErrorTree::from_external_error(
+                            row_start,
+                            nom::error::ErrorKind::Verify,
+                            GridError::RaggedRow {
+                                row: height,
+                                expected: length,
+                                found: row.len(),
+                            },
+                        )));
                     }
+                    break;
                 }
+                inner.append(&mut row);
+                height += 1;
+                rest = row_end;
+            }
+            Ok((
+                rest,
+                Self {
+                    inner,
+                    length,
+                    height,
+                },
+            ))
+        }
+    }
+
+    /// Collects rows into a grid, failing on the first row whose length differs from the first.
+    pub fn try_from_rows<J, I>(rows: I) -> Result<Self, GridError>
+    where
+        I: IntoIterator<Item = J>,
+        J: IntoIterator<Item = T>,
+    {
+        let mut inner = Vec::new();
+        let mut length = 0;
+        let mut height = 0;
+        for row in rows {
+            let row_start = inner.len();
+            inner.extend(row);
+            let found = inner.len() - row_start;
+            if height == 0 {
+                length = found;
+            } else if found != length {
+                return Err(GridError::RaggedRow {
+                    row: height,
+                    expected: length,
+                    found,
+                });
+            }
+            height += 1;
+        }
+        Ok(Self {
+            inner,
+            length,
+            height,
+        })
+    }
+}
+
+impl<T> Index<GridPos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: GridPos) -> &Self::Output {
+        self.get(&pos).unwrap_or_else(|| {
+            panic!(
+                "position ({}, {}) out of bounds for a {}x{} grid",
+                pos.x, pos.y, self.length, self.height
+            )
+        })
+    }
+}
+
+impl<T> IndexMut<GridPos> for Grid<T> {
+    fn index_mut(&mut self, pos: GridPos) -> &mut Self::Output {
+        let (length, height) = (self.length, self.height);
+        self.get_mut(&pos).unwrap_or_else(|| {
+            panic!(
+                "position ({}, {}) out of bounds for a {length}x{height} grid",
+                pos.x, pos.y
+            )
+        })
+    }
+}
+
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.iter_rows() {
+            for t in row {
+                t.fmt(f)?;
             }
-            Err(e) => Err(e),
+            writeln!(f)?;
         }
+        Ok(())
     }
 }
 
@@ -141,33 +340,111 @@ impl<J, T> FromIterator<J> for Grid<T>
 where
     J: Iterator<Item = T>,
 {
+    /// Panics on ragged rows, use [`Grid::try_from_rows`] to handle them.
     fn from_iter<I: IntoIterator<Item = J>>(iter: I) -> Self {
-        let mut iter = iter.into_iter();
-        let mut height = 0;
-        if let Some(first) = iter.next() {
-            let mut acc = first.collect_vec();
-            let length = acc.len();
-            for row in iter {
-                acc.extend(row);
-                if acc.len() - height * length != length {
-                    panic!("Expected {length} elements")
-                }
-                height += 1;
-            }
-            Self {
-                inner: acc,
-                height,
-                length,
-            }
-        } else {
-            Self {
-                inner: Vec::new(),
-                length: 0,
-                height: 0,
-            }
-        }
+        Self::try_from_rows(iter).unwrap_or_else(|e| panic!("{e}"))
     }
 }
 
 pub type ParseResult<'a, T> = IResult<&'a str, T, ErrorTree<&'a str>>;
 pub type ParseFinalResult<'a, T> = Result<T, ErrorTree<Location>>;
+
+#[cfg(test)]
+mod test {
+    use nom::{character::complete::one_of, multi::many1};
+    use nom_supreme::error::BaseErrorKind;
+
+    use super::*;
+
+    fn row(input: &str) -> ParseResult<'_, Vec<char>> {
+        many1(one_of(".#")).parse(input)
+    }
+
+    #[test]
+    fn parse_returns_remaining_input() {
+        let (rest, grid) = Grid::parse(row).parse("#.\n.#\nrest").unwrap();
+        assert_eq!(rest, "\nrest");
+        assert_eq!((grid.length, grid.height), (2, 2));
+        assert_eq!(grid[GridPos { x: 1, y: 1 }], '#');
+    }
+
+    #[test]
+    fn parse_leaves_trailing_newlines() {
+        for strict in [false, true] {
+            let (rest, grid) = Grid::parse_rows(row, strict).parse("#.\n.#\n\n").unwrap();
+            assert_eq!(rest, "\n\n");
+            assert_eq!(grid.height, 2);
+        }
+    }
+
+    #[test]
+    fn parse_stops_at_ragged_row() {
+        let (rest, grid) = Grid::parse(row).parse("##\n..\n#\n##").unwrap();
+        assert_eq!(rest, "\n#\n##");
+        assert_eq!(grid.height, 2);
+    }
+
+    #[test]
+    fn parse_strict_reports_ragged_row() {
+        let Err(nom::Err::Failure(ErrorTree::Base {
+            location,
+            kind: BaseErrorKind::External(error),
+        })) = Grid::parse_strict(row).parse("##\n..\n#\n##")
+        else {
+            panic!("expected a failure");
+        };
+        assert_eq!(location, "#\n##");
+        assert_eq!(
+            error.downcast_ref::<GridError>(),
+            Some(&GridError::RaggedRow {
+                row: 2,
+                expected: 2,
+                found: 1
+            })
+        );
+    }
+
+    #[test]
+    fn from_iter_counts_all_rows() {
+        let grid: Grid<u8> = [[1, 2], [3, 4], [5, 6]]
+            .into_iter()
+            .map(|row| row.into_iter())
+            .collect();
+        assert_eq!((grid.length, grid.height), (2, 3));
+        assert_eq!(grid[GridPos { x: 0, y: 2 }], 5);
+    }
+
+    #[test]
+    fn try_from_rows_reports_ragged_row() {
+        let rows = vec![vec![1, 2], vec![3, 4], vec![5]];
+        assert_eq!(
+            Grid::try_from_rows(rows),
+            Err(GridError::RaggedRow {
+                row: 2,
+                expected: 2,
+                found: 1
+            })
+        );
+    }
+
+    #[test]
+    fn from_str_errors() {
+        let cell = |c| (c == '.').then_some(());
+        assert_eq!(
+            Grid::from_str("..\n.x\n", cell),
+            Err(GridError::UnexpectedChar {
+                c: 'x',
+                pos: GridPos { x: 1, y: 1 }
+            })
+        );
+        assert_eq!(
+            Grid::from_str("..\n.\n", cell),
+            Err(GridError::RaggedRow {
+                row: 1,
+                expected: 2,
+                found: 1
+            })
+        );
+        assert_eq!(Grid::from_str("..\n..\n", cell).map(|g| g.height), Ok(2));
+    }
+}
@@ -0,0 +1,239 @@
+use std::collections::VecDeque;
+
+use super::{bitgrid::BitGrid, prelude::*};
+
+/// Statistics of one connected region of cells.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region {
+    /// The first cell of the region in row-major order.
+    pub start: GridPos,
+    pub area: usize,
+    /// The number of cell edges between the region and anything else, including the grid border.
+    pub perimeter: usize,
+    /// The number of straight fence sides, which equals the number of corners.
+    pub sides: usize,
+    /// The top left corner of the bounding box.
+    pub min: GridPos,
+    /// The bottom right corner of the bounding box, inclusive.
+    pub max: GridPos,
+    /// The number of areas of other cells that are completely enclosed by the region.
+    pub holes: usize,
+}
+
+/// A labelling of the 4-connected regions of a grid.
+#[derive(Debug, Clone)]
+pub struct Regions {
+    labels: Grid<Option<usize>>,
+    regions: Vec<Region>,
+}
+
+impl Regions {
+    /// Splits the whole grid into regions of neighbouring cells for which `same` holds.
+    pub fn partition<T>(grid: &Grid<T>, mut same: impl FnMut(&T, &T) -> bool) -> Self {
+        Self::label(grid, |_| true, |a, b| same(a, b))
+    }
+
+    /// The regions of neighbouring cells that satisfy `include`. Other cells get no label.
+    pub fn matching<T>(grid: &Grid<T>, mut include: impl FnMut(&T) -> bool) -> Self {
+        let included = grid.map(|cell| include(cell));
+        Self::label(&included, |cell| *cell, |_, _| true)
+    }
+
+    fn label<T>(
+        grid: &Grid<T>,
+        include: impl FnMut(&T) -> bool,
+        same: impl FnMut(&T, &T) -> bool,
+    ) -> Self {
+        let (labels, starts) = flood_fill(grid, include, same);
+
+        let mut regions = starts
+            .into_iter()
+            .map(|start| Region {
+                start,
+                area: 0,
+                perimeter: 0,
+                sides: 0,
+                min: start,
+                max: start,
+                holes: 0,
+            })
+            .collect_vec();
+        for (pos, label) in labels.enumerate() {
+            let Some(label) = *label else {
+                continue;
+            };
+            let same_region = |heading: Heading| {
+                pos.step(heading)
+                    .and_then(|next_pos| labels.get(&next_pos))
+                    .is_some_and(|other| *other == Some(label))
+            };
+            let region = &mut regions[label];
+            region.area += 1;
+            region.perimeter += Direction::ALL
+                .into_iter()
+                .filter(|direction| !same_region((*direction).into()))
+                .count();
+            // Every corner lies between a side and the side clockwise from it, and is either
+            // convex (both sides are fenced) or concave (only the diagonal is fenced).
+            region.sides += Direction::ALL
+                .into_iter()
+                .filter(|direction| {
+                    let side = same_region((*direction).into());
+                    let next_side = same_region(direction.turn_right().into());
+                    let diagonal = same_region(Heading::from(*direction).turn_right());
+                    (!side && !next_side) || (side && next_side && !diagonal)
+                })
+                .count();
+            region.min = GridPos {
+                x: region.min.x.min(pos.x),
+                y: region.min.y.min(pos.y),
+            };
+            region.max = GridPos {
+                x: region.max.x.max(pos.x),
+                y: region.max.y.max(pos.y),
+            };
+        }
+        for (label, region) in regions.iter_mut().enumerate() {
+            region.holes = count_holes(&labels, label, region.min, region.max);
+        }
+        Self { labels, regions }
+    }
+
+    /// The label of the region containing `pos`, which indexes [`Regions::regions`].
+    pub fn label_at(&self, pos: &GridPos) -> Option<usize> {
+        *self.labels.get(pos)?
+    }
+
+    pub fn regions(&self) -> &[Region] {
+        &self.regions
+    }
+
+    /// The cells of the region with the given label.
+    pub fn cells(&self, label: usize) -> impl Iterator<Item = GridPos> + '_ {
+        self.labels
+            .enumerate()
+            .filter(move |(_, other)| **other == Some(label))
+            .map(|(pos, _)| pos)
+    }
+}
+
+/// Labels the connected components of included cells and returns the first cell of each.
+fn flood_fill<T>(
+    grid: &Grid<T>,
+    mut include: impl FnMut(&T) -> bool,
+    mut same: impl FnMut(&T, &T) -> bool,
+) -> (Grid<Option<usize>>, Vec<GridPos>) {
+    let mut labels = Grid::new(grid.length, grid.height, None);
+    let mut starts = Vec::new();
+    let mut queue = VecDeque::new();
+    for (start, cell) in grid.enumerate() {
+        if labels[start].is_some() || !include(cell) {
+            continue;
+        }
+        let label = starts.len();
+        starts.push(start);
+        labels[start] = Some(label);
+        queue.push_back(start);
+        while let Some(pos) = queue.pop_front() {
+            for (next_pos, next) in grid.neighbors(&pos) {
+                if labels[next_pos].is_none() && include(next) && same(&grid[pos], next) {
+                    labels[next_pos] = Some(label);
+                    queue.push_back(next_pos);
+                }
+            }
+        }
+    }
+    (labels, starts)
+}
+
+/// Counts the 4-connected areas of cells inside the bounding box that are not part of the
+/// region and cannot reach the box's border. Areas touching only at a corner count separately,
+/// just like their fences do.
+fn count_holes(labels: &Grid<Option<usize>>, label: usize, min: GridPos, max: GridPos) -> usize {
+    let length = max.x - min.x + 1;
+    let height = max.y - min.y + 1;
+    let other = |pos: &GridPos| {
+        labels[GridPos {
+            x: min.x + pos.x,
+            y: min.y + pos.y,
+        }] != Some(label)
+    };
+    let mut seen = BitGrid::new(length, height);
+    let mut stack = Vec::new();
+    let mut fill = |start: GridPos, seen: &mut BitGrid| {
+        stack.push(start);
+        while let Some(pos) = stack.pop() {
+            stack.extend(
+                pos.neighbors(length, height)
+                    .filter(|next_pos| other(next_pos) && seen.insert(*next_pos)),
+            );
+        }
+    };
+
+    let border = (0..length)
+        .flat_map(|x| [GridPos { x, y: 0 }, GridPos { x, y: height - 1 }])
+        .chain((0..height).flat_map(|y| [GridPos { x: 0, y }, GridPos { x: length - 1, y }]));
+    for start in border {
+        if other(&start) && seen.insert(start) {
+            fill(start, &mut seen);
+        }
+    }
+    let mut holes = 0;
+    for y in 1..height.saturating_sub(1) {
+        for x in 1..length - 1 {
+            let start = GridPos { x, y };
+            if other(&start) && seen.insert(start) {
+                holes += 1;
+                fill(start, &mut seen);
+            }
+        }
+    }
+    holes
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn garden(input: &str) -> Regions {
+        let grid = Grid::from_str(input, Some).unwrap();
+        Regions::partition(&grid, |a, b| a == b)
+    }
+
+    #[test]
+    fn holes() {
+        let regions = garden(include_str!("../days/test_input/day12_holes.txt"));
+        assert_eq!(regions.regions().len(), 5);
+        let outer = &regions.regions()[0];
+        assert_eq!(outer.area, 21);
+        assert_eq!(outer.perimeter, 36);
+        assert_eq!(outer.sides, 20);
+        assert_eq!(outer.holes, 4);
+        assert_eq!(
+            (outer.min, outer.max),
+            (GridPos { x: 0, y: 0 }, GridPos { x: 4, y: 4 })
+        );
+        assert_eq!(regions.regions()[1].holes, 0);
+    }
+
+    #[test]
+    fn touching_holes() {
+        let regions = garden(include_str!("../days/test_input/day12_touching_holes.txt"));
+        let outer = &regions.regions()[0];
+        assert_eq!(outer.area, 28);
+        assert_eq!(outer.sides, 12);
+        assert_eq!(outer.holes, 2);
+        assert_eq!(regions.label_at(&GridPos { x: 4, y: 1 }), Some(1));
+        assert_eq!(regions.cells(1).count(), 4);
+    }
+
+    #[test]
+    fn matching() {
+        let grid = Grid::from_str("#.#\n#.#\n##.\n", |c| Some(c == '#')).unwrap();
+        let regions = Regions::matching(&grid, |wall| *wall);
+        assert_eq!(regions.regions().len(), 2);
+        assert_eq!(regions.label_at(&GridPos { x: 1, y: 0 }), None);
+        assert_eq!(regions.regions()[0].area, 4);
+        assert_eq!(regions.regions()[1].area, 2);
+    }
+}
@@ -0,0 +1,362 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+    hash::Hash,
+    ops::Add,
+};
+
+/// Breadth first search from all `starts`, returning the number of steps to every reachable node.
+pub fn bfs<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut neighbors: impl FnMut(&N) -> I,
+) -> HashMap<N, usize>
+where
+    N: Hash + Eq + Clone,
+    I: IntoIterator<Item = N>,
+{
+    let mut distances = HashMap::new();
+    let mut queue = VecDeque::new();
+    for start in starts {
+        if distances.insert(start.clone(), 0).is_none() {
+            queue.push_back(start);
+        }
+    }
+    while let Some(node) = queue.pop_front() {
+        let distance = distances[&node] + 1;
+        for next in neighbors(&node) {
+            if !distances.contains_key(&next) {
+                distances.insert(next.clone(), distance);
+                queue.push_back(next);
+            }
+        }
+    }
+    distances
+}
+
+/// Interns nodes so the heap and the predecessor lists only deal in indices.
+#[derive(Debug, Clone)]
+struct NodeIndex<N> {
+    nodes: Vec<N>,
+    positions: HashMap<N, usize>,
+}
+
+impl<N: Hash + Eq + Clone> NodeIndex<N> {
+    fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            positions: HashMap::new(),
+        }
+    }
+
+    fn intern(&mut self, node: N) -> (usize, bool) {
+        if let Some(ix) = self.positions.get(&node) {
+            return (*ix, false);
+        }
+        self.nodes.push(node.clone());
+        self.positions.insert(node, self.nodes.len() - 1);
+        (self.nodes.len() - 1, true)
+    }
+}
+
+/// The result of [`dijkstra`]: distances to all settled nodes and, for each of them, all
+/// predecessors on an optimal path, i.e. the DAG of all shortest paths.
+#[derive(Debug, Clone)]
+pub struct ShortestPaths<N, C> {
+    index: NodeIndex<N>,
+    distances: Vec<Option<C>>,
+    predecessors: Vec<Vec<usize>>,
+    settle_order: Vec<usize>,
+    goals: Vec<usize>,
+}
+
+/// Dijkstra's algorithm from all `starts` over strictly positive edge costs.
+///
+/// Positive costs guarantee that every predecessor is settled before its successor, which the
+/// predecessor lists, [`ShortestPaths::nodes_on_paths_to`] and [`ShortestPaths::path_counts`]
+/// rely on. With zero costs, equally short paths through a node settled later would be lost,
+/// and a zero-cost cycle has no finite path count. Panics on an edge cost that is not positive.
+///
+/// The search stops once every node as close as the first goal is settled, so all goals at the
+/// optimal distance are found. Pass `|_| false` to explore everything reachable.
+pub fn dijkstra<N, C, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut neighbors: impl FnMut(&N) -> I,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> ShortestPaths<N, C>
+where
+    N: Hash + Eq + Clone,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let mut result = ShortestPaths {
+        index: NodeIndex::new(),
+        distances: Vec::new(),
+        predecessors: Vec::new(),
+        settle_order: Vec::new(),
+        goals: Vec::new(),
+    };
+    let mut tentative: Vec<C> = Vec::new();
+    let mut queue = BinaryHeap::new();
+    for start in starts {
+        let (ix, new) = result.index.intern(start);
+        if new {
+            tentative.push(C::default());
+            result.distances.push(None);
+            result.predecessors.push(Vec::new());
+            queue.push((Reverse(C::default()), ix));
+        }
+    }
+
+    while let Some((Reverse(distance), ix)) = queue.pop() {
+        if result.distances[ix].is_some() {
+            continue;
+        }
+        if let Some(goal) = result.goals.first() {
+            if result.distances[*goal].is_some_and(|goal_distance| distance > goal_distance) {
+                break;
+            }
+        }
+        result.distances[ix] = Some(distance);
+        result.settle_order.push(ix);
+        let node = result.index.nodes[ix].clone();
+        if is_goal(&node) {
+            result.goals.push(ix);
+        }
+        for (next, cost) in neighbors(&node) {
+            assert!(cost > C::default(), "edge costs must be positive");
+            let next_distance = distance + cost;
+            let (next_ix, new) = result.index.intern(next);
+            if new {
+                tentative.push(next_distance);
+                result.distances.push(None);
+                result.predecessors.push(vec![ix]);
+                queue.push((Reverse(next_distance), next_ix));
+                continue;
+            }
+            if result.distances[next_ix].is_some() {
+                continue;
+            }
+            match next_distance.cmp(&tentative[next_ix]) {
+                std::cmp::Ordering::Less => {
+                    tentative[next_ix] = next_distance;
+                    result.predecessors[next_ix] = vec![ix];
+                    queue.push((Reverse(next_distance), next_ix));
+                }
+                std::cmp::Ordering::Equal => result.predecessors[next_ix].push(ix),
+                std::cmp::Ordering::Greater => {}
+            }
+        }
+    }
+    result
+}
+
+impl<N, C> ShortestPaths<N, C>
+where
+    N: Hash + Eq + Clone,
+    C: Copy,
+{
+    fn ix(&self, node: &N) -> Option<usize> {
+        self.index
+            .positions
+            .get(node)
+            .copied()
+            .filter(|ix| self.distances[*ix].is_some())
+    }
+
+    /// The distance to a settled node.
+    pub fn distance(&self, node: &N) -> Option<C> {
+        self.distances[self.ix(node)?]
+    }
+
+    /// All settled nodes with their distances, in the order they were settled.
+    pub fn distances(&self) -> impl Iterator<Item = (&N, C)> + '_ {
+        self.settle_order.iter().map(|ix| {
+            (
+                &self.index.nodes[*ix],
+                self.distances[*ix].expect("settled"),
+            )
+        })
+    }
+
+    /// The goals reached at the optimal distance.
+    pub fn goals(&self) -> impl Iterator<Item = &N> + '_ {
+        self.goals.iter().map(|ix| &self.index.nodes[*ix])
+    }
+
+    /// The distance to the closest goal.
+    pub fn goal_distance(&self) -> Option<C> {
+        self.distances[*self.goals.first()?]
+    }
+
+    /// The predecessors of a settled node on any of its shortest paths.
+    pub fn predecessors(&self, node: &N) -> impl Iterator<Item = &N> + '_ {
+        let predecessors = match self.ix(node) {
+            Some(ix) => &self.predecessors[ix][..],
+            None => &[],
+        };
+        predecessors.iter().map(|ix| &self.index.nodes[*ix])
+    }
+
+    /// One shortest path from a start to `node`, both included.
+    pub fn path_to(&self, node: &N) -> Option<Vec<N>> {
+        let mut ix = self.ix(node)?;
+        let mut path = vec![self.index.nodes[ix].clone()];
+        while let Some(parent) = self.predecessors[ix].first() {
+            ix = *parent;
+            path.push(self.index.nodes[ix].clone());
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// All nodes lying on some shortest path to one of the `targets`.
+    pub fn nodes_on_paths_to<'a>(&self, targets: impl IntoIterator<Item = &'a N>) -> HashSet<N>
+    where
+        N: 'a,
+    {
+        let mut seen = HashSet::new();
+        let mut stack = targets
+            .into_iter()
+            .filter_map(|target| self.ix(target))
+            .collect::<Vec<_>>();
+        while let Some(ix) = stack.pop() {
+            if seen.insert(ix) {
+                stack.extend(self.predecessors[ix].iter().copied());
+            }
+        }
+        seen.into_iter()
+            .map(|ix| self.index.nodes[ix].clone())
+            .collect()
+    }
+
+    /// The number of distinct shortest paths from any start to each settled node.
+    pub fn path_counts(&self) -> HashMap<N, usize> {
+        let mut counts = vec![0; self.index.nodes.len()];
+        for ix in &self.settle_order {
+            counts[*ix] = if self.predecessors[*ix].is_empty() {
+                1
+            } else {
+                self.predecessors[*ix]
+                    .iter()
+                    .map(|parent| counts[*parent])
+                    .sum()
+            };
+        }
+        self.settle_order
+            .iter()
+            .map(|ix| (self.index.nodes[*ix].clone(), counts[*ix]))
+            .collect()
+    }
+}
+
+/// A* search for the cheapest path from `start` to a goal.
+///
+/// The `heuristic` must never overestimate the remaining cost and be consistent, e.g. a
+/// manhattan distance on a grid with unit steps. Returns the cost and the path, both ends included.
+pub fn astar<N, C, I>(
+    start: N,
+    mut neighbors: impl FnMut(&N) -> I,
+    mut heuristic: impl FnMut(&N) -> C,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> Option<(C, Vec<N>)>
+where
+    N: Hash + Eq + Clone,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let mut index = NodeIndex::new();
+    let mut costs = vec![C::default()];
+    let mut parents: Vec<Option<usize>> = vec![None];
+    let mut closed = vec![false];
+    let mut queue = BinaryHeap::new();
+    let (start_ix, _) = index.intern(start);
+    queue.push((Reverse(heuristic(&index.nodes[start_ix])), start_ix));
+
+    while let Some((_, ix)) = queue.pop() {
+        if closed[ix] {
+            continue;
+        }
+        closed[ix] = true;
+        let node = index.nodes[ix].clone();
+        if is_goal(&node) {
+            let mut path = vec![node];
+            let mut current = ix;
+            while let Some(parent) = parents[current] {
+                path.push(index.nodes[parent].clone());
+                current = parent;
+            }
+            path.reverse();
+            return Some((costs[ix], path));
+        }
+        for (next, cost) in neighbors(&node) {
+            let next_cost = costs[ix] + cost;
+            let (next_ix, new) = index.intern(next);
+            if new {
+                costs.push(next_cost);
+                parents.push(Some(ix));
+                closed.push(false);
+            } else if closed[next_ix] || next_cost >= costs[next_ix] {
+                continue;
+            } else {
+                costs[next_ix] = next_cost;
+                parents[next_ix] = Some(ix);
+            }
+            let estimate = next_cost + heuristic(&index.nodes[next_ix]);
+            queue.push((Reverse(estimate), next_ix));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// A diamond `0 -> {1, 2} -> 3` with a detour `0 -> 4 -> 3` that is one longer.
+    fn diamond(node: &u8) -> Vec<(u8, u32)> {
+        match node {
+            0 => vec![(1, 1), (2, 1), (4, 1)],
+            1 | 2 => vec![(3, 1)],
+            4 => vec![(3, 2)],
+            _ => vec![],
+        }
+    }
+
+    #[test]
+    fn bfs_distances() {
+        let distances = bfs([0], |node| diamond(node).into_iter().map(|(n, _)| n));
+        assert_eq!(distances[&3], 2);
+        assert_eq!(distances[&4], 1);
+    }
+
+    #[test]
+    fn dijkstra_dag() {
+        let paths = dijkstra([0], diamond, |node| *node == 3);
+        assert_eq!(paths.goal_distance(), Some(2));
+        assert_eq!(paths.path_to(&3).map(|path| path.len()), Some(3));
+        assert_eq!(
+            paths.nodes_on_paths_to([&3]),
+            [0, 1, 2, 3].into_iter().collect()
+        );
+        assert_eq!(paths.path_counts()[&3], 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "edge costs must be positive")]
+    fn dijkstra_rejects_zero_costs() {
+        dijkstra(
+            [0u8],
+            |node| (*node < 2).then_some((node + 1, 0u32)),
+            |_| false,
+        );
+    }
+
+    #[test]
+    fn astar_path() {
+        let (cost, path) = astar(0u8, diamond, |_| 0, |node| *node == 3).unwrap();
+        assert_eq!(cost, 2);
+        assert_eq!(path.first(), Some(&0));
+        assert_eq!(path.last(), Some(&3));
+        assert_eq!(astar(1u8, diamond, |_| 0, |node| *node == 4), None);
+    }
+}
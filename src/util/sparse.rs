@@ -0,0 +1,208 @@
+use std::collections::{hash_map, HashMap};
+
+use super::prelude::*;
+
+/// A position on an unbounded grid, `[x, y]` with `y` pointing down.
+pub type SparsePos = [i32; 2];
+
+impl From<GridPos> for SparsePos {
+    fn from(value: GridPos) -> Self {
+        [value.x as i32, value.y as i32]
+    }
+}
+
+/// Moves one cell in the given heading.
+pub fn step(pos: SparsePos, heading: impl Into<Heading>) -> SparsePos {
+    let [dx, dy] = heading.into().delta();
+    [pos[0] + dx as i32, pos[1] + dy as i32]
+}
+
+/// A grid that only stores occupied cells, with signed coordinates.
+///
+/// The bounding box of all cells ever inserted is tracked on insertion, so it never shrinks when
+/// cells are removed or move away.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SparseGrid<T> {
+    cells: HashMap<SparsePos, T>,
+    bounds: Option<[SparsePos; 2]>,
+}
+
+impl<T> Default for SparseGrid<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new() -> Self {
+        Self {
+            cells: HashMap::new(),
+            bounds: None,
+        }
+    }
+
+    /// Keeps the cells of a dense grid for which `keep` holds, at the same coordinates.
+    pub fn from_grid(grid: &Grid<T>, mut keep: impl FnMut(&T) -> bool) -> Self
+    where
+        T: Clone,
+    {
+        grid.enumerate()
+            .filter(|(_, cell)| keep(cell))
+            .map(|(pos, cell)| (pos.into(), cell.clone()))
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn insert(&mut self, pos: SparsePos, value: T) -> Option<T> {
+        self.bounds = Some(match self.bounds {
+            None => [pos, pos],
+            Some([min, max]) => [
+                [min[0].min(pos[0]), min[1].min(pos[1])],
+                [max[0].max(pos[0]), max[1].max(pos[1])],
+            ],
+        });
+        self.cells.insert(pos, value)
+    }
+
+    pub fn remove(&mut self, pos: &SparsePos) -> Option<T> {
+        self.cells.remove(pos)
+    }
+
+    pub fn get(&self, pos: &SparsePos) -> Option<&T> {
+        self.cells.get(pos)
+    }
+
+    pub fn get_mut(&mut self, pos: &SparsePos) -> Option<&mut T> {
+        self.cells.get_mut(pos)
+    }
+
+    pub fn contains(&self, pos: &SparsePos) -> bool {
+        self.cells.contains_key(pos)
+    }
+
+    pub fn iter(&self) -> hash_map::Iter<'_, SparsePos, T> {
+        self.cells.iter()
+    }
+
+    pub fn positions(&self) -> hash_map::Keys<'_, SparsePos, T> {
+        self.cells.keys()
+    }
+
+    /// The top left and bottom right corner of all cells ever inserted, both inclusive.
+    pub fn bounds(&self) -> Option<[SparsePos; 2]> {
+        self.bounds
+    }
+
+    /// The number of columns between the leftmost and rightmost cell, both included.
+    pub fn width(&self) -> usize {
+        self.bounds
+            .map_or(0, |[min, max]| (max[0] - min[0]) as usize + 1)
+    }
+
+    /// The number of rows between the topmost and bottommost cell, both included.
+    pub fn height(&self) -> usize {
+        self.bounds
+            .map_or(0, |[min, max]| (max[1] - min[1]) as usize + 1)
+    }
+
+    /// The occupied cells next to `pos` in the four directions.
+    pub fn neighbors(&self, pos: SparsePos) -> impl Iterator<Item = (SparsePos, &T)> + '_ {
+        Direction::ALL.into_iter().filter_map(move |direction| {
+            let next_pos = step(pos, direction);
+            self.get(&next_pos).map(|cell| (next_pos, cell))
+        })
+    }
+
+    /// A dense grid covering the bounds, with the top left corner at `GridPos { x: 0, y: 0 }`.
+    pub fn to_grid(&self, empty: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let Some([min, max]) = self.bounds else {
+            return Grid::new(0, 0, empty);
+        };
+        (min[1]..=max[1])
+            .map(|y| {
+                (min[0]..=max[0])
+                    .map(|x| self.get(&[x, y]).cloned().unwrap_or_else(|| empty.clone()))
+                    .collect_vec()
+                    .into_iter()
+            })
+            .collect()
+    }
+
+    /// Renders the bounds as text, one line per row.
+    pub fn render(&self, mut cell: impl FnMut(Option<&T>) -> char) -> String {
+        let Some([min, max]) = self.bounds else {
+            return String::new();
+        };
+        let mut result = String::new();
+        for y in min[1]..=max[1] {
+            result.extend((min[0]..=max[0]).map(|x| cell(self.get(&[x, y]))));
+            result.push('\n');
+        }
+        result
+    }
+}
+
+impl<'a, T> IntoIterator for &'a SparseGrid<T> {
+    type Item = (&'a SparsePos, &'a T);
+    type IntoIter = hash_map::Iter<'a, SparsePos, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T> FromIterator<(SparsePos, T)> for SparseGrid<T> {
+    fn from_iter<I: IntoIterator<Item = (SparsePos, T)>>(iter: I) -> Self {
+        let mut grid = Self::new();
+        grid.extend(iter);
+        grid
+    }
+}
+
+impl<T> Extend<(SparsePos, T)> for SparseGrid<T> {
+    fn extend<I: IntoIterator<Item = (SparsePos, T)>>(&mut self, iter: I) {
+        for (pos, value) in iter {
+            self.insert(pos, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn bounds_only_grow() {
+        let mut grid = SparseGrid::new();
+        assert_eq!((grid.width(), grid.height()), (0, 0));
+        grid.insert([-2, 1], 'a');
+        grid.insert([3, -1], 'b');
+        assert_eq!(grid.bounds(), Some([[-2, -1], [3, 1]]));
+        assert_eq!((grid.width(), grid.height()), (6, 3));
+        grid.remove(&[3, -1]);
+        assert_eq!(grid.width(), 6);
+        assert_eq!(
+            grid.render(|c| *c.unwrap_or(&'.')),
+            "......\n......\na.....\n"
+        );
+    }
+
+    #[test]
+    fn dense_round_trip() {
+        let dense = Grid::from_str("#..\n.##\n", |c| Some(c == '#')).unwrap();
+        let sparse = SparseGrid::from_grid(&dense, |wall| *wall);
+        assert_eq!(sparse.len(), 3);
+        assert_eq!(sparse.neighbors([1, 1]).count(), 1);
+        assert_eq!(sparse.to_grid(false), dense);
+    }
+}
@@ -0,0 +1,95 @@
+use super::{prelude::*, vec2::Vec2};
+
+/// A grid whose opposite edges are glued together, so every position wraps around.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Torus {
+    pub size: Vec2,
+}
+
+impl Torus {
+    pub fn new(width: i64, height: i64) -> Self {
+        assert!(width > 0 && height > 0, "a torus needs a positive size");
+        Self {
+            size: Vec2::new(width, height),
+        }
+    }
+
+    /// The equivalent position inside `[0, width) x [0, height)`.
+    pub fn wrap(&self, pos: Vec2) -> Vec2 {
+        pos.rem_euclid(self.size)
+    }
+
+    /// Where a mover starting at `pos` ends up after `times` steps of `velocity`.
+    pub fn advance(&self, pos: Vec2, velocity: Vec2, times: i64) -> Vec2 {
+        // Wrapping the velocity first keeps the product small for large step counts.
+        let velocity = self.wrap(velocity);
+        let times = Vec2::new(times.rem_euclid(self.size.x), times.rem_euclid(self.size.y));
+        self.wrap(pos + Vec2::new(velocity.x * times.x, velocity.y * times.y))
+    }
+
+    /// Splits an axis of length `size` into `k` equal parts and returns the part containing
+    /// cell `c`, or `None` if a boundary passes through the cell's interior.
+    fn part(c: i64, size: i64, k: i64) -> Option<i64> {
+        let part = c * k / size;
+        ((c + 1) * k <= (part + 1) * size).then_some(part)
+    }
+
+    /// The sector of a `k` x `k` division that contains `pos`, as `[column, row]`. Cells that a
+    /// sector boundary cuts through belong to no sector, like the middle row and column of an
+    /// odd-sized torus for `k = 2`.
+    pub fn sector(&self, pos: Vec2, k: i64) -> Option<[i64; 2]> {
+        let pos = self.wrap(pos);
+        Some([
+            Self::part(pos.x, self.size.x, k)?,
+            Self::part(pos.y, self.size.y, k)?,
+        ])
+    }
+
+    /// The quadrant containing `pos`, or `None` on a midline.
+    pub fn quadrant(&self, pos: Vec2) -> Option<[i64; 2]> {
+        self.sector(pos, 2)
+    }
+
+    /// The number of positions that wrap onto each cell.
+    pub fn counts(&self, positions: impl IntoIterator<Item = Vec2>) -> Grid<usize> {
+        let mut counts = Grid::new(self.size.x as usize, self.size.y as usize, 0);
+        for pos in positions {
+            let pos = self.wrap(pos);
+            counts[GridPos {
+                x: pos.x as usize,
+                y: pos.y as usize,
+            }] += 1;
+        }
+        counts
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn wrapping() {
+        let torus = Torus::new(11, 7);
+        assert_eq!(torus.wrap(Vec2::new(-1, 7)), Vec2::new(10, 0));
+        let (pos, velocity) = (Vec2::new(2, 4), Vec2::new(2, -3));
+        let stepped = (0..5).fold(pos, |pos, _| torus.wrap(pos + velocity));
+        assert_eq!(torus.advance(pos, velocity, 5), stepped);
+        assert_eq!(stepped, Vec2::new(1, 3));
+        assert_eq!(torus.advance(pos, velocity, 77), pos);
+    }
+
+    #[test]
+    fn sectors() {
+        let odd = Torus::new(11, 7);
+        assert_eq!(odd.quadrant(Vec2::new(4, 2)), Some([0, 0]));
+        assert_eq!(odd.quadrant(Vec2::new(5, 2)), None);
+        assert_eq!(odd.quadrant(Vec2::new(6, 4)), Some([1, 1]));
+        let even = Torus::new(6, 6);
+        assert_eq!(even.sector(Vec2::new(1, 2), 3), Some([0, 1]));
+        assert_eq!(even.sector(Vec2::new(5, 5), 4), Some([3, 3]));
+        assert_eq!(even.sector(Vec2::new(1, 0), 4), None);
+        let counts = even.counts([Vec2::new(1, 1), Vec2::new(7, -5)]);
+        assert_eq!(counts[GridPos { x: 1, y: 1 }], 2);
+    }
+}
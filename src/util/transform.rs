@@ -0,0 +1,290 @@
+use std::ops::Index;
+
+use super::prelude::*;
+
+/// One of the eight symmetries of a rectangle: an optional transposition followed by optional
+/// mirroring of the columns and rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Transform {
+    transpose: bool,
+    flip_x: bool,
+    flip_y: bool,
+}
+
+impl Transform {
+    pub const IDENTITY: Self = Self::new(false, false, false);
+    /// Rotates clockwise by 90 degrees.
+    pub const ROTATE_RIGHT: Self = Self::new(true, true, false);
+    pub const ROTATE_180: Self = Self::new(false, true, true);
+    /// Rotates counterclockwise by 90 degrees.
+    pub const ROTATE_LEFT: Self = Self::new(true, false, true);
+    /// Mirrors along the main diagonal, swapping `x` and `y`.
+    pub const TRANSPOSE: Self = Self::new(true, false, false);
+    /// Mirrors left and right.
+    pub const FLIP_HORIZONTAL: Self = Self::new(false, true, false);
+    /// Mirrors top and bottom.
+    pub const FLIP_VERTICAL: Self = Self::new(false, false, true);
+    /// Mirrors along the anti-diagonal.
+    pub const ANTI_TRANSPOSE: Self = Self::new(true, true, true);
+
+    /// The four rotations, starting with the identity.
+    pub const ROTATIONS: [Self; 4] = [
+        Self::IDENTITY,
+        Self::ROTATE_RIGHT,
+        Self::ROTATE_180,
+        Self::ROTATE_LEFT,
+    ];
+    /// All rotations and reflections.
+    pub const ALL: [Self; 8] = [
+        Self::IDENTITY,
+        Self::ROTATE_RIGHT,
+        Self::ROTATE_180,
+        Self::ROTATE_LEFT,
+        Self::TRANSPOSE,
+        Self::FLIP_HORIZONTAL,
+        Self::FLIP_VERTICAL,
+        Self::ANTI_TRANSPOSE,
+    ];
+
+    const fn new(transpose: bool, flip_x: bool, flip_y: bool) -> Self {
+        Self {
+            transpose,
+            flip_x,
+            flip_y,
+        }
+    }
+
+    /// The size of the transformed grid.
+    pub fn size(self, length: usize, height: usize) -> (usize, usize) {
+        if self.transpose {
+            (height, length)
+        } else {
+            (length, height)
+        }
+    }
+
+    /// The position in the original grid of `length` x `height` that ends up at `pos`.
+    pub fn source(self, pos: GridPos, length: usize, height: usize) -> GridPos {
+        let (view_length, view_height) = self.size(length, height);
+        let x = if self.flip_x {
+            view_length - 1 - pos.x
+        } else {
+            pos.x
+        };
+        let y = if self.flip_y {
+            view_height - 1 - pos.y
+        } else {
+            pos.y
+        };
+        if self.transpose {
+            GridPos { x: y, y: x }
+        } else {
+            GridPos { x, y }
+        }
+    }
+}
+
+/// A transformed view of a grid, without copying it.
+#[derive(Debug)]
+pub struct GridView<'a, T> {
+    grid: &'a Grid<T>,
+    transform: Transform,
+    pub length: usize,
+    pub height: usize,
+}
+
+impl<T> Clone for GridView<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for GridView<'_, T> {}
+
+impl<'a, T> GridView<'a, T> {
+    pub fn get(&self, pos: &GridPos) -> Option<&'a T> {
+        if pos.x < self.length && pos.y < self.height {
+            self.grid.get(
+                &self
+                    .transform
+                    .source(*pos, self.grid.length, self.grid.height),
+            )
+        } else {
+            None
+        }
+    }
+
+    /// All cells in reading order of the view together with their positions in the view.
+    pub fn enumerate(&self) -> impl Iterator<Item = (GridPos, &'a T)> + 'a {
+        let view = *self;
+        (0..view.height)
+            .flat_map(move |y| (0..view.length).map(move |x| GridPos { x, y }))
+            .map(move |pos| (pos, view.get(&pos).expect("in bounds")))
+    }
+
+    pub fn to_grid(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        (0..self.height)
+            .map(|y| (0..self.length).map(move |x| self[GridPos { x, y }].clone()))
+            .collect()
+    }
+}
+
+impl<T> Index<GridPos> for GridView<'_, T> {
+    type Output = T;
+
+    fn index(&self, pos: GridPos) -> &Self::Output {
+        self.get(&pos).unwrap_or_else(|| {
+            panic!(
+                "position ({}, {}) out of bounds for a {}x{} view",
+                pos.x, pos.y, self.length, self.height
+            )
+        })
+    }
+}
+
+/// The cells from a start position in one heading until the edge of the grid.
+#[derive(Debug, Clone)]
+pub struct Ray<'a, T> {
+    grid: &'a Grid<T>,
+    pos: Option<GridPos>,
+    heading: Heading,
+}
+
+impl<'a, T> Iterator for Ray<'a, T> {
+    type Item = (GridPos, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let pos = self.pos?;
+        let cell = self.grid.get(&pos)?;
+        self.pos = pos.step(self.heading);
+        Some((pos, cell))
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn view(&self, transform: Transform) -> GridView<'_, T> {
+        let (length, height) = transform.size(self.length, self.height);
+        GridView {
+            grid: self,
+            transform,
+            length,
+            height,
+        }
+    }
+
+    pub fn rotate_right(&self) -> GridView<'_, T> {
+        self.view(Transform::ROTATE_RIGHT)
+    }
+
+    pub fn rotate_left(&self) -> GridView<'_, T> {
+        self.view(Transform::ROTATE_LEFT)
+    }
+
+    pub fn rotate_180(&self) -> GridView<'_, T> {
+        self.view(Transform::ROTATE_180)
+    }
+
+    pub fn transpose(&self) -> GridView<'_, T> {
+        self.view(Transform::TRANSPOSE)
+    }
+
+    pub fn flip_horizontal(&self) -> GridView<'_, T> {
+        self.view(Transform::FLIP_HORIZONTAL)
+    }
+
+    pub fn flip_vertical(&self) -> GridView<'_, T> {
+        self.view(Transform::FLIP_VERTICAL)
+    }
+
+    /// The cells from `start` in the given heading, starting with `start` itself.
+    pub fn ray(&self, start: GridPos, heading: impl Into<Heading>) -> Ray<'_, T> {
+        Ray {
+            grid: self,
+            pos: Some(start),
+            heading: heading.into(),
+        }
+    }
+
+    /// All maximal lines in the given heading, each starting at the edge of the grid.
+    pub fn lines(&self, heading: impl Into<Heading>) -> impl Iterator<Item = Ray<'_, T>> + '_ {
+        let heading = heading.into();
+        self.enumerate()
+            .map(|(pos, _)| pos)
+            .filter(move |pos| {
+                pos.step(heading.opposite())
+                    .filter(|previous| self.contains(previous))
+                    .is_none()
+            })
+            .map(move |start| self.ray(start, heading))
+    }
+
+    /// The rows from left to right.
+    pub fn rows(&self) -> impl Iterator<Item = Ray<'_, T>> + '_ {
+        self.lines(Heading::East)
+    }
+
+    /// The columns from top to bottom.
+    pub fn columns(&self) -> impl Iterator<Item = Ray<'_, T>> + '_ {
+        self.lines(Heading::South)
+    }
+
+    /// The diagonals from top left to bottom right.
+    pub fn diagonals(&self) -> impl Iterator<Item = Ray<'_, T>> + '_ {
+        self.lines(Heading::SouthEast)
+    }
+
+    /// The anti-diagonals from top right to bottom left.
+    pub fn anti_diagonals(&self) -> impl Iterator<Item = Ray<'_, T>> + '_ {
+        self.lines(Heading::SouthWest)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn text<'a>(cells: impl Iterator<Item = (GridPos, &'a char)>) -> String {
+        cells.map(|(_, c)| *c).collect()
+    }
+
+    #[test]
+    fn views() {
+        let grid = Grid::from_str("abc\ndef\n", Some).unwrap();
+        assert_eq!(grid.rotate_right().to_grid().to_string(), "da\neb\nfc\n");
+        assert_eq!(grid.rotate_left().to_grid().to_string(), "cf\nbe\nad\n");
+        assert_eq!(grid.rotate_180().to_grid().to_string(), "fed\ncba\n");
+        assert_eq!(grid.transpose().to_grid().to_string(), "ad\nbe\ncf\n");
+        assert_eq!(grid.flip_horizontal().to_grid().to_string(), "cba\nfed\n");
+        assert_eq!(grid.flip_vertical().to_grid().to_string(), "def\nabc\n");
+        let views = Transform::ALL.map(|transform| grid.view(transform).to_grid());
+        assert!(views.iter().tuple_combinations().all(|(a, b)| a != b));
+        let rotated = grid.rotate_right().to_grid();
+        assert_eq!(
+            rotated.rotate_right().to_grid(),
+            grid.rotate_180().to_grid()
+        );
+    }
+
+    #[test]
+    fn lines() {
+        let grid = Grid::from_str("abc\ndef\n", Some).unwrap();
+        assert_eq!(grid.rows().map(text).collect_vec(), ["abc", "def"]);
+        assert_eq!(grid.columns().map(text).collect_vec(), ["ad", "be", "cf"]);
+        assert_eq!(
+            grid.diagonals().map(text).collect_vec(),
+            ["ae", "bf", "c", "d"]
+        );
+        assert_eq!(
+            grid.anti_diagonals().map(text).collect_vec(),
+            ["a", "bd", "ce", "f"]
+        );
+        assert_eq!(text(grid.ray(GridPos { x: 2, y: 1 }, Heading::West)), "fed");
+        assert_eq!(
+            text(grid.ray(GridPos { x: 1, y: 1 }, Heading::NorthEast)),
+            "ec"
+        );
+    }
+}